@@ -29,57 +29,49 @@
 use std::fmt::{Display, Formatter};
 
 use bpx::macros::impl_err_conversion;
+use bpxtools::package::error::{PackError, UnpackError};
 
-pub enum UnpackError
+pub enum Error
 {
-    Bpxp(bpx::package::error::ReadError),
-    Io(std::io::Error),
-    Strings(bpx::strings::ReadError)
+    Dump(bpxtools::dump::error::Error),
+    Unpack(UnpackError),
+    Pack(PackError),
+    Usage(String)
 }
 
 impl_err_conversion!(
-    UnpackError {
-        bpx::package::error::ReadError => Bpxp,
-        std::io::Error => Io,
-        bpx::strings::ReadError => Strings
+    Error {
+        bpxtools::dump::error::Error => Dump,
+        UnpackError => Unpack,
+        PackError => Pack
     }
 );
 
-pub enum PackError
+impl Error
 {
-    Bpxp(bpx::package::error::WriteError),
-    Bpx(bpx::core::error::WriteError),
-    Io(std::io::Error)
-}
-
-impl_err_conversion!(
-    PackError {
-        bpx::package::error::WriteError => Bpxp,
-        bpx::core::error::WriteError => Bpx,
-        std::io::Error => Io
-    }
-);
-
-impl Display for PackError
-{
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result
+    //Like most command line tools, usage errors exit with 2 and failed operations with 1. diff
+    //follows diff(1) instead: 1 means that the files differ, so any error exits with 2
+    pub fn exit_code(&self, command: &str) -> i32
     {
         match self {
-            PackError::Bpxp(e) => write!(f, "BPXP error: {}", e),
-            PackError::Bpx(e) => write!(f, "BPX error: {}", e),
-            PackError::Io(e) => write!(f, "IO error: {}", e)
+            Error::Usage(_) => 2,
+            _ if command == "diff" => 2,
+            _ => 1
         }
     }
 }
 
-impl Display for UnpackError
+impl Display for Error
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result
     {
         match self {
-            UnpackError::Bpxp(e) => write!(f, "BPXP error: {}", e),
-            UnpackError::Io(e) => write!(f, "IO error: {}", e),
-            UnpackError::Strings(e) => write!(f, "Strings error: {}", e)
+            Error::Dump(e) => e.fmt(f),
+            Error::Unpack(e) => e.fmt(f),
+            Error::Pack(e) => e.fmt(f),
+            Error::Usage(s) => write!(f, "Usage error: {}", s)
         }
     }
 }
+
+pub type Result<T> = std::result::Result<T, Error>;
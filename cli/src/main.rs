@@ -0,0 +1,804 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{io::IsTerminal, path::Path};
+
+use bpxtools::{
+    dump::{
+        bpxdiff,
+        bpxinfo::{self, PrintOptions}
+    },
+    package::{
+        archive,
+        check,
+        diff,
+        error::UnpackError,
+        info,
+        input::InputOptions,
+        list::{self, ListOptions, Sort},
+        merge::{self, MergeOptions, Policy},
+        metadata,
+        pack::{self, Architecture, BuildOptions, Checksum, Compression, PackOptions, Platform},
+        split::{self, SplitOptions},
+        unpack::{self, UnpackOptions},
+        update::{self, Change, UpdateOptions},
+        ProgressMode
+    }
+};
+use clap::{clap_app, ArgMatches};
+
+use crate::error::{Error, Result};
+
+mod error;
+mod print;
+
+const PACKAGE_TYPE: u8 = b'P';
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Format
+{
+    Text,
+    Json,
+    Csv
+}
+
+//Options accepted by every command
+struct Global<'a>
+{
+    file: &'a Path,
+    format: Format,
+    verbose: bool
+}
+
+impl<'a> Global<'a>
+{
+    fn read(matches: &'a ArgMatches) -> Result<Global<'a>>
+    {
+        let file = match matches.value_of("file") {
+            Some(v) => Path::new(v),
+            None => {
+                return Err(Error::Usage(
+                    "please specify the BPX file to operate on with -f".into()
+                ))
+            },
+        };
+        let format = match matches.value_of("format") {
+            Some("json") => Format::Json,
+            Some("csv") => Format::Csv,
+            _ => Format::Text
+        };
+        Ok(Global {
+            file,
+            format,
+            verbose: matches.is_present("verbose")
+        })
+    }
+
+    fn require_text(&self, command: &str) -> Result<()>
+    {
+        if self.format != Format::Text {
+            return Err(Error::Usage(format!(
+                "{} only supports --format text",
+                command
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn parse_section(matches: &ArgMatches) -> Result<u32>
+{
+    let value = matches.value_of("section").unwrap();
+    value
+        .parse()
+        .map_err(|e| Error::Usage(format!("could not parse section index '{}' ({})", value, e)))
+}
+
+fn parse_u32(matches: &ArgMatches, name: &str) -> Result<Option<u32>>
+{
+    match matches.value_of(name) {
+        None => Ok(None),
+        Some(v) => match v.parse() {
+            Ok(v) => Ok(Some(v)),
+            Err(e) => Err(Error::Usage(format!(
+                "could not parse {} '{}' ({})",
+                name, v, e
+            )))
+        }
+    }
+}
+
+//Accepts a number of bytes with an optional K, M or G suffix
+fn parse_size(value: &str) -> Result<u64>
+{
+    let (digits, multiplier) = match value.chars().last() {
+        Some('K') | Some('k') => (&value[..value.len() - 1], 1 << 10),
+        Some('M') | Some('m') => (&value[..value.len() - 1], 1 << 20),
+        Some('G') | Some('g') => (&value[..value.len() - 1], 1 << 30),
+        _ => (value, 1)
+    };
+    match digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|v| v.checked_mul(multiplier))
+    {
+        Some(v) if v > 0 => Ok(v),
+        _ => Err(Error::Usage(format!("invalid size '{}'", value)))
+    }
+}
+
+//Falls back to the SOURCE_DATE_EPOCH environment variable, see
+//https://reproducible-builds.org/specs/source-date-epoch/
+fn source_date_epoch(matches: &ArgMatches) -> Result<Option<i64>>
+{
+    let value = match matches.value_of("source_date_epoch") {
+        Some(v) => v.into(),
+        None => match std::env::var("SOURCE_DATE_EPOCH") {
+            Ok(v) => v,
+            Err(_) => return Ok(None)
+        }
+    };
+    match value.trim().parse() {
+        Ok(v) => Ok(Some(v)),
+        Err(e) => Err(Error::Usage(format!(
+            "could not parse source date epoch '{}' ({})",
+            value, e
+        )))
+    }
+}
+
+fn validate_jobs(value: String) -> std::result::Result<(), String>
+{
+    match value.parse::<usize>() {
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("could not parse job count '{}' ({})", value, e))
+    }
+}
+
+//The value is checked by validate_jobs, 0 stands for one thread per CPU
+fn jobs(matches: &ArgMatches) -> usize
+{
+    matches
+        .value_of("jobs")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(1)
+}
+
+//Values of the following options are restricted by clap
+fn architecture(matches: &ArgMatches) -> Option<Architecture>
+{
+    matches.value_of("arch").map(|v| match v {
+        "x86_64" => Architecture::X86_64,
+        "aarch64" => Architecture::Aarch64,
+        "x86" => Architecture::X86,
+        "armv7hl" => Architecture::Armv7hl,
+        _ => Architecture::Any
+    })
+}
+
+fn platform(matches: &ArgMatches) -> Option<Platform>
+{
+    matches.value_of("platform").map(|v| match v {
+        "linux" => Platform::Linux,
+        "mac" => Platform::Mac,
+        "windows" => Platform::Windows,
+        "android" => Platform::Android,
+        _ => Platform::Any
+    })
+}
+
+fn compression(matches: &ArgMatches) -> Option<Compression>
+{
+    matches.value_of("compression").map(|v| match v {
+        "zlib" => Compression::Zlib,
+        "xz" => Compression::Xz,
+        _ => Compression::None
+    })
+}
+
+fn checksum(matches: &ArgMatches) -> Option<Checksum>
+{
+    matches.value_of("checksum").map(|v| match v {
+        "weak" => Checksum::Weak,
+        "crc32" => Checksum::Crc32,
+        _ => Checksum::None
+    })
+}
+
+fn progress(matches: &ArgMatches) -> ProgressMode
+{
+    match matches.value_of("progress") {
+        Some("json") => ProgressMode::Json,
+        Some("none") => ProgressMode::None,
+        _ => ProgressMode::Auto
+    }
+}
+
+fn run_info(global: &Global, matches: &ArgMatches) -> Result<()>
+{
+    let package = bpxinfo::container_type(global.file)? == PACKAGE_TYPE;
+    if matches.is_present("metadata") {
+        global.require_text("info --metadata")?;
+        if !package {
+            return Err(Error::Usage("--metadata only applies to BPXP files".into()));
+        }
+        print::info(&info::read(global.file)?);
+        return Ok(());
+    }
+    let options = PrintOptions {
+        header: true,
+        metadata: true,
+        hex: matches.is_present("hex"),
+        ..Default::default()
+    };
+    match global.format {
+        Format::Json => {
+            let mut json = bpxinfo::to_json(global.file, &options)?;
+            if package {
+                json["metadata"] = info::read(global.file)?.metadata.into();
+            }
+            println!("{}", json);
+        },
+        Format::Text => {
+            bpxinfo::run(global.file, &options, &mut std::io::stdout().lock())?;
+            if package {
+                print::metadata(&info::read(global.file)?.metadata);
+            }
+        },
+        Format::Csv => return Err(Error::Usage("info does not support --format csv".into()))
+    }
+    Ok(())
+}
+
+fn run_sht(global: &Global) -> Result<()>
+{
+    let options = PrintOptions {
+        sht: true,
+        ..Default::default()
+    };
+    match global.format {
+        Format::Json => println!("{}", bpxinfo::to_json(global.file, &options)?),
+        Format::Text => bpxinfo::run(global.file, &options, &mut std::io::stdout().lock())?,
+        Format::Csv => return Err(Error::Usage("sht does not support --format csv".into()))
+    }
+    Ok(())
+}
+
+fn run_dump(global: &Global, matches: &ArgMatches) -> Result<()>
+{
+    global.require_text("dump")?;
+    let options = PrintOptions {
+        section: Some(parse_section(matches)?),
+        hex: matches.is_present("hex"),
+        output: matches.value_of("output").map(Into::into),
+        force: matches.is_present("force"),
+        ..Default::default()
+    };
+    bpxinfo::run(global.file, &options, &mut std::io::stdout().lock())?;
+    Ok(())
+}
+
+fn run_sd(global: &Global, matches: &ArgMatches) -> Result<()>
+{
+    let section = parse_section(matches)?;
+    match global.format {
+        Format::Json => {
+            let object = bpxinfo::read_sd(global.file, section)?;
+            println!("{}", metadata::to_json(&object));
+        },
+        Format::Text => {
+            let options = PrintOptions {
+                section: Some(section),
+                bpxsd: true,
+                ..Default::default()
+            };
+            bpxinfo::run(global.file, &options, &mut std::io::stdout().lock())?;
+        },
+        Format::Csv => return Err(Error::Usage("sd does not support --format csv".into()))
+    }
+    Ok(())
+}
+
+fn run_verify(global: &Global, matches: &ArgMatches) -> Result<()>
+{
+    global.require_text("verify")?;
+    let tested = check::run(global.file, jobs(matches))?;
+    for v in &tested {
+        print::tested(v);
+    }
+    let failed = tested.iter().filter(|v| v.res.is_err()).count();
+    if failed > 0 {
+        return Err(UnpackError::TestFailed(failed).into());
+    }
+    println!("No errors detected in {}", global.file.display());
+    Ok(())
+}
+
+fn run_diff(global: &Global, matches: &ArgMatches) -> Result<bool>
+{
+    global.require_text("diff")?;
+    let other = Path::new(matches.value_of("other").unwrap());
+    let package = bpxinfo::container_type(global.file)? == PACKAGE_TYPE;
+    if package && !matches.is_present("container") {
+        let diffs = diff::run(global.file, other, matches.is_present("unified"))?;
+        print::package_diff(&diffs);
+        Ok(!diffs.is_empty())
+    } else {
+        let diffs = bpxdiff::run(global.file, other)?;
+        print::container_diff(&diffs, other);
+        Ok(!diffs.is_empty())
+    }
+}
+
+fn build_options<'a>(matches: &'a ArgMatches) -> Result<BuildOptions<'a>>
+{
+    Ok(BuildOptions {
+        generator: matches.value_of("generator"),
+        arch: architecture(matches),
+        platform: platform(matches),
+        compression: compression(matches),
+        level: parse_u32(matches, "level")?,
+        threshold: parse_u32(matches, "threshold")?,
+        checksum: checksum(matches)
+    })
+}
+
+fn input_options<'a>(matches: &'a ArgMatches) -> InputOptions<'a>
+{
+    InputOptions {
+        prefix: matches.value_of("prefix"),
+        base_dir: matches.value_of("base_dir").map(Path::new),
+        follow_links: matches.is_present("no_preserve")
+    }
+}
+
+fn pack_options<'a>(matches: &'a ArgMatches) -> Result<PackOptions<'a>>
+{
+    let preserve = !matches.is_present("no_preserve");
+    Ok(PackOptions {
+        build: build_options(matches)?,
+        input: input_options(matches),
+        files_from: matches.value_of("files_from"),
+        metadata: matches.value_of("metadata").map(Path::new),
+        hashes: matches.is_present("hashes"),
+        preserve,
+        source_date_epoch: if preserve {
+            source_date_epoch(matches)?
+        } else {
+            None
+        },
+        dry_run: matches.is_present("dry_run"),
+        progress: progress(matches)
+    })
+}
+
+fn values<'a>(matches: &'a ArgMatches, name: &str) -> Vec<&'a str>
+{
+    matches
+        .values_of(name)
+        .map(|v| v.collect())
+        .unwrap_or_default()
+}
+
+fn run_pack(global: &Global, matches: &ArgMatches) -> Result<()>
+{
+    global.require_text("pack")?;
+    let options = pack_options(matches)?;
+    let packed = pack::run(global.file, &values(matches, "files"), &options, |v| {
+        if global.verbose {
+            println!("Packing {} as {}...", v.source.display(), v.name);
+        }
+    })?;
+    if options.dry_run {
+        let compressed = options.build.compression != Some(Compression::None);
+        print::dry_run(&packed, matches.is_present("human"), compressed);
+    }
+    Ok(())
+}
+
+fn run_update(global: &Global, matches: &ArgMatches) -> Result<()>
+{
+    global.require_text("update")?;
+    let options = UpdateOptions {
+        add: values(matches, "add"),
+        replace: values(matches, "replace"),
+        delete: values(matches, "delete"),
+        input: input_options(matches),
+        preserve: !matches.is_present("no_preserve"),
+        source_date_epoch: source_date_epoch(matches)?
+    };
+    if options.add.is_empty() && options.replace.is_empty() && options.delete.is_empty() {
+        return Err(Error::Usage(
+            "please specify objects to change with --add, --replace or --delete".into()
+        ));
+    }
+    let changes = update::run(global.file, &options)?;
+    if global.verbose {
+        for v in changes {
+            match v {
+                Change::Deleted(name) => println!("Deleting {}...", name),
+                Change::Replaced(name, source) => {
+                    println!("Replacing {} with {}...", name, source.display())
+                },
+                Change::Added(source, name) => {
+                    println!("Adding {} as {}...", source.display(), name)
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_import(global: &Global, matches: &ArgMatches) -> Result<()>
+{
+    global.require_text("import")?;
+    let options = pack_options(matches)?;
+    let imported = archive::import(
+        global.file,
+        Path::new(matches.value_of("archive").unwrap()),
+        &options
+    )?;
+    if global.verbose {
+        for v in imported {
+            match v.copy_of {
+                Some(target) => println!("Importing {} as a copy of {}...", v.name, target),
+                None => println!("Importing {}...", v.name)
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_export(global: &Global, matches: &ArgMatches) -> Result<()>
+{
+    global.require_text("export")?;
+    let exported = archive::export(
+        global.file,
+        Path::new(matches.value_of("archive").unwrap()),
+        !matches.is_present("no_preserve")
+    )?;
+    if global.verbose {
+        for v in exported {
+            println!(
+                "Exporting object name {} with {} byte(s)...",
+                v.name, v.size
+            );
+        }
+    }
+    Ok(())
+}
+
+fn run_merge(global: &Global, matches: &ArgMatches) -> Result<()>
+{
+    global.require_text("merge")?;
+    let options = MergeOptions {
+        build: build_options(matches)?,
+        policy: match matches.value_of("on_conflict") {
+            Some("first") => Policy::First,
+            Some("last") => Policy::Last,
+            _ => Policy::Error
+        }
+    };
+    let packages = values(matches, "packages");
+    let report = merge::run(global.file, &packages, &options)?;
+    if global.verbose {
+        if report.copied {
+            println!("Copying objects of {} as stored...", packages[0]);
+        }
+        for (name, from) in report.recompressed {
+            println!("Recompressing {} from {}...", name, from);
+        }
+    }
+    Ok(())
+}
+
+fn run_split(global: &Global, matches: &ArgMatches) -> Result<()>
+{
+    global.require_text("split")?;
+    let options = SplitOptions {
+        build: build_options(matches)?,
+        rules: values(matches, "rule"),
+        rest: matches.value_of("rest").map(Path::new),
+        max_size: match matches.value_of("max_size") {
+            Some(v) => Some(parse_size(v)?),
+            None => None
+        }
+    };
+    let report = split::run(global.file, &options)?;
+    if global.verbose {
+        if report.copied {
+            println!("Copying objects of {} as stored...", global.file.display());
+        }
+        for v in report.outputs {
+            if !report.copied {
+                for name in &v.objects {
+                    println!("Recompressing {} from {}...", name, global.file.display());
+                }
+            }
+            println!(
+                "Wrote {} object(s) to {}",
+                v.objects.len(),
+                v.path.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+fn run_unpack(global: &Global, matches: &ArgMatches) -> Result<()>
+{
+    global.require_text("unpack")?;
+    let options = UnpackOptions {
+        target: Path::new(matches.value_of("directory").unwrap_or(".")),
+        preserve: !matches.is_present("no_preserve"),
+        progress: progress(matches),
+        jobs: jobs(matches)
+    };
+    unpack::run(global.file, &options, |v| {
+        if global.verbose {
+            println!(
+                "Unpacking object name {} with {} byte(s)...",
+                v.name, v.size
+            );
+        }
+    })?;
+    Ok(())
+}
+
+fn run_cat(global: &Global, matches: &ArgMatches) -> Result<()>
+{
+    global.require_text("cat")?;
+    let stdout = std::io::stdout();
+    if stdout.is_terminal() && !matches.is_present("force") {
+        return Err(UnpackError::BinaryOutput.into());
+    }
+    unpack::cat(
+        global.file,
+        matches.value_of("name").unwrap(),
+        stdout.lock()
+    )?;
+    Ok(())
+}
+
+fn run_list(global: &Global, matches: &ArgMatches) -> Result<()>
+{
+    let hashes = matches.is_present("hashes");
+    let options = ListOptions {
+        hashes,
+        sort: match matches.value_of("sort") {
+            Some("name") => Some(Sort::Name),
+            Some("size") => Some(Sort::Size),
+            _ => None
+        }
+    };
+    let entries = list::run(global.file, &options)?;
+    let human = matches.is_present("human");
+    match global.format {
+        Format::Json => print::list_json(&entries, hashes),
+        Format::Csv => print::list_csv(&entries, hashes),
+        Format::Text => {
+            if matches.is_present("tree") {
+                print::list_tree(&entries, human);
+            } else if matches.is_present("long") {
+                print::list_long(&entries, hashes, human);
+            } else {
+                print::list_short(&entries, hashes);
+            }
+            print::metadata(&info::read(global.file)?.metadata);
+        }
+    }
+    Ok(())
+}
+
+//Returns whether the compared files differ, other commands always return false
+fn run(name: &str, sub: &ArgMatches) -> Result<bool>
+{
+    //Global options are propagated to the matches of each command
+    let global = Global::read(sub)?;
+    let res = match name {
+        "info" => run_info(&global, sub),
+        "sht" => run_sht(&global),
+        "dump" => run_dump(&global, sub),
+        "sd" => run_sd(&global, sub),
+        "verify" => run_verify(&global, sub),
+        "pack" => run_pack(&global, sub),
+        "unpack" => run_unpack(&global, sub),
+        "list" => run_list(&global, sub),
+        "cat" => run_cat(&global, sub),
+        "update" => run_update(&global, sub),
+        "import" => run_import(&global, sub),
+        "export" => run_export(&global, sub),
+        "merge" => run_merge(&global, sub),
+        "split" => run_split(&global, sub),
+        "diff" => return run_diff(&global, sub),
+        _ => Err(Error::Usage(format!("unknown command '{}'", name)))
+    };
+    res.map(|()| false)
+}
+
+fn main()
+{
+    let app = clap_app!(bpx =>
+        (version: "1.0")
+        (author: "BlockProject3D <https://github.com/BlockProject3D>")
+        (about: "Inspects, verifies, creates, edits and extracts BPX files")
+        (@setting SubcommandRequiredElseHelp)
+        (@setting VersionlessSubcommands)
+        (@arg file: -f --file +takes_value +global "Path to the BPX file to operate on")
+        (@arg format: --format +takes_value +global possible_value[text json csv] "Output format (json is supported by info, sht, sd and list, csv by list only)")
+        (@arg verbose: -v --verbose +global "Print the objects packed, unpacked or copied by the command")
+        (@subcommand info =>
+            (about: "Print the main header and TypeExt of a BPX file, and the metadata of a BPXP")
+            (@arg hex: -x --hex "Print the TypeExt block in hex")
+            (@arg metadata: --metadata "Only print the target, generator and metadata of a BPXP")
+        )
+        (@subcommand sht =>
+            (about: "Print the section header table (SHT)")
+        )
+        (@subcommand dump =>
+            (about: "Dump the content of a section")
+            (@arg section: +required "Index of the section to dump")
+            (@arg hex: -x --hex "Print data in hex")
+            (@arg output: -o --output +takes_value "Save dump output to a file")
+            (@arg force: --force "Force printing binary data to terminal ignoring potential terminal destruction")
+        )
+        (@subcommand sd =>
+            (about: "Print a section as a BPX Structured Data Object (BPXSD)")
+            (@arg section: +required "Index of the section to print")
+        )
+        (@subcommand verify =>
+            (about: "Verify all sections, and decode all objects of a BPXP, without writing anything")
+            (@arg jobs: -j --jobs +takes_value {validate_jobs} "Number of worker threads (0 uses one thread per CPU, defaults to 1)")
+        )
+        (@subcommand pack =>
+            (about: "Create a BPXP from files and directories (on a single thread, the BPXP encoder compresses sections one after the other)")
+            (@arg compression: --compression +takes_value possible_value[none zlib xz] "Compression algorithm used for the sections of the package")
+            (@arg level: --level +takes_value "Compression level passed to the compression algorithm")
+            (@arg threshold: --threshold +takes_value "Size in bytes under which section data is stored uncompressed")
+            (@arg checksum: --checksum +takes_value possible_value[none weak crc32] "Checksum algorithm used to verify the sections of the package")
+            (@arg arch: --arch +takes_value possible_value[x86_64 aarch64 x86 armv7hl any] "Target architecture of the package (defaults to any)")
+            (@arg platform: --platform +takes_value possible_value[linux mac windows android any] "Target platform of the package (defaults to any)")
+            (@arg generator: --generator +takes_value "2 characters code identifying the tool which generated the package (defaults to BD)")
+            (@arg metadata: --metadata +takes_value "Path to a JSON file containing the metadata object to embed")
+            (@arg hashes: --hashes "Store the SHA-256 of each object")
+            (@arg no_preserve: --("no-preserve") "Do not store file permissions, modification times and symbolic links")
+            (@arg source_date_epoch: --("source-date-epoch") +takes_value "Clamp stored modification times to the given UNIX timestamp for reproducible builds (defaults to the SOURCE_DATE_EPOCH environment variable)")
+            (@arg prefix: --prefix +takes_value "Virtual directory to prepend to the name of each packed object")
+            (@arg base_dir: --("base-dir") +takes_value "Name packed objects relative to the given directory instead of using their file name")
+            (@arg files_from: -T --("files-from") +takes_value "Read the files to pack from a list (one per line or NUL-separated, - for standard input) or from a TOML/JSON manifest (paths relative to the manifest)")
+            (@arg dry_run: --("dry-run") "Print the objects which would be packed and an estimate of the package size without writing it")
+            (@arg human: -H --human "Print sizes in human readable units")
+            (@arg progress: --progress +takes_value possible_value[auto json none] "Progress display on standard error (auto shows it only on a terminal)")
+            (@arg files: ... "List of files/objects to pack (use src=dest to choose the object name of src)")
+        )
+        (@subcommand unpack =>
+            (about: "Extract all objects of a BPXP")
+            (@arg directory: -C --directory +takes_value "Directory to extract to (defaults to the current directory)")
+            (@arg no_preserve: --("no-preserve") "Do not restore file permissions, modification times and symbolic links")
+            (@arg jobs: -j --jobs +takes_value {validate_jobs} "Number of worker threads (0 uses one thread per CPU, defaults to 1)")
+            (@arg progress: --progress +takes_value possible_value[auto json none] "Progress display on standard error (auto shows it only on a terminal)")
+        )
+        (@subcommand list =>
+            (about: "List all objects contained in a BPXP")
+            (@arg long: --long "List objects with their stored size, compression ratio and sections")
+            (@arg tree: --tree "List objects as a tree grouped by directory")
+            (@arg sort: --sort +takes_value possible_value[name size] "Sort listed objects by name or by decreasing size")
+            (@arg hashes: --hashes "Print the SHA-256 of each object")
+            (@arg human: -H --human "Print sizes in human readable units")
+        )
+        (@subcommand cat =>
+            (about: "Write an object of a BPXP to standard output")
+            (@arg name: +required "Name of the object")
+            (@arg force: --force "Force writing to a terminal ignoring potential terminal destruction")
+        )
+        (@subcommand update =>
+            (about: "Add, replace and delete objects of a BPXP in place")
+            (@arg add: --add +takes_value +multiple "Files to add (use src=dest to choose the object name of src)")
+            (@arg replace: --replace +takes_value +multiple "Files replacing objects (use src=dest to replace an object named dest)")
+            (@arg delete: --delete +takes_value +multiple "Names of the objects to delete")
+            (@arg no_preserve: --("no-preserve") "Do not store file permissions, modification times and symbolic links of the added and replacing files")
+            (@arg source_date_epoch: --("source-date-epoch") +takes_value "Clamp stored modification times to the given UNIX timestamp for reproducible builds (defaults to the SOURCE_DATE_EPOCH environment variable)")
+            (@arg prefix: --prefix +takes_value "Virtual directory to prepend to the name of each added object")
+            (@arg base_dir: --("base-dir") +takes_value "Name added objects relative to the given directory instead of using their file name")
+        )
+        (@subcommand import =>
+            (about: "Create a BPXP from a tar, tar.gz or zip archive")
+            (@arg archive: +required "Path to the archive to import")
+            (@arg compression: --compression +takes_value possible_value[none zlib xz] "Compression algorithm used for the sections of the package")
+            (@arg level: --level +takes_value "Compression level passed to the compression algorithm")
+            (@arg threshold: --threshold +takes_value "Size in bytes under which section data is stored uncompressed")
+            (@arg checksum: --checksum +takes_value possible_value[none weak crc32] "Checksum algorithm used to verify the sections of the package")
+            (@arg arch: --arch +takes_value possible_value[x86_64 aarch64 x86 armv7hl any] "Target architecture of the package (defaults to any)")
+            (@arg platform: --platform +takes_value possible_value[linux mac windows android any] "Target platform of the package (defaults to any)")
+            (@arg generator: --generator +takes_value "2 characters code identifying the tool which generated the package (defaults to BD)")
+            (@arg metadata: --metadata +takes_value "Path to a JSON file containing the metadata object to embed")
+            (@arg hashes: --hashes "Store the SHA-256 of each object")
+            (@arg no_preserve: --("no-preserve") "Do not store file permissions and modification times")
+            (@arg source_date_epoch: --("source-date-epoch") +takes_value "Clamp the modification times of the archive entries to the given UNIX timestamp for reproducible builds (defaults to the SOURCE_DATE_EPOCH environment variable)")
+        )
+        (@subcommand export =>
+            (about: "Write all objects of a BPXP to a tar or zip archive")
+            (@arg archive: +required "Path to the archive to create")
+            (@arg no_preserve: --("no-preserve") "Do not restore file permissions, modification times and symbolic links")
+        )
+        (@subcommand merge =>
+            (about: "Merge the objects of several BPXP into the BPXP given by -f (the sections of the first package are copied as stored unless another target, compression or checksum is requested, objects of the other packages are recompressed)")
+            (@arg on_conflict: --("on-conflict") +takes_value possible_value[error first last] "What to do with objects and metadata keys present in several packages (defaults to error)")
+            (@arg compression: --compression +takes_value possible_value[none zlib xz] "Compression algorithm used for the sections of the package")
+            (@arg level: --level +takes_value "Compression level passed to the compression algorithm")
+            (@arg threshold: --threshold +takes_value "Size in bytes under which section data is stored uncompressed")
+            (@arg checksum: --checksum +takes_value possible_value[none weak crc32] "Checksum algorithm used to verify the sections of the package")
+            (@arg arch: --arch +takes_value possible_value[x86_64 aarch64 x86 armv7hl any] "Target architecture of the package (defaults to the one of the merged packages)")
+            (@arg platform: --platform +takes_value possible_value[linux mac windows android any] "Target platform of the package (defaults to the one of the merged packages)")
+            (@arg generator: --generator +takes_value "2 characters code identifying the tool which generated the package")
+            (@arg packages: +required +multiple "Paths to the BPX files to merge")
+        )
+        (@subcommand split =>
+            (about: "Split a BPXP into several BPXP by object name patterns or by size (sections are copied as stored unless another target, compression or checksum is requested)")
+            (@arg rule: -r --rule +takes_value +multiple conflicts_with[max_size] "Write the objects matching a glob pattern to another BPXP (use pattern=output.bpx, '**' matches any number of directories)")
+            (@arg rest: --rest +takes_value requires[rule] "Path to the BPX file receiving objects matching no rule")
+            (@arg max_size: --("max-size") +takes_value "Maximum size of each output BPXP in bytes (accepts K, M and G suffixes), outputs are numbered after the input file; nothing is written when an output would be larger")
+            (@arg compression: --compression +takes_value possible_value[none zlib xz] "Compression algorithm used for the sections of the outputs")
+            (@arg level: --level +takes_value "Compression level passed to the compression algorithm")
+            (@arg threshold: --threshold +takes_value "Size in bytes under which section data is stored uncompressed")
+            (@arg checksum: --checksum +takes_value possible_value[none weak crc32] "Checksum algorithm used to verify the sections of the outputs")
+            (@arg arch: --arch +takes_value possible_value[x86_64 aarch64 x86 armv7hl any] "Target architecture of the outputs (defaults to the one of the split package)")
+            (@arg platform: --platform +takes_value possible_value[linux mac windows android any] "Target platform of the outputs (defaults to the one of the split package)")
+            (@arg generator: --generator +takes_value "2 characters code identifying the tool which generated the outputs")
+        )
+        (@subcommand diff =>
+            (about: "Compare the BPX file given by -f with another one; exits with 1 when they differ and with 2 on error, like diff(1)")
+            (@arg other: +required "Path to the BPX file to compare with")
+            (@arg unified: -U --unified "Print a unified diff of changed text objects")
+            (@arg container: --container "Compare headers, TypeExt, SHT and section data instead of the objects of two BPXP")
+        )
+    );
+    let matches = match app.get_matches_safe() {
+        Ok(v) => v,
+        Err(e) if e.use_stderr() => {
+            eprintln!("{}", e.message);
+            std::process::exit(2);
+        },
+        Err(e) => e.exit()
+    };
+
+    let (name, sub) = match matches.subcommand() {
+        (name, Some(sub)) => (name, sub),
+        _ => {
+            eprintln!("{}", Error::Usage("please specify a command".into()));
+            std::process::exit(2);
+        }
+    };
+    match run(name, sub) {
+        Ok(differ) => std::process::exit(i32::from(differ)),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(e.exit_code(name));
+        }
+    }
+}
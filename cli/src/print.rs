@@ -0,0 +1,315 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Text, JSON and CSV rendering of the results returned by the library.
+
+use std::{collections::BTreeMap, path::Path};
+
+use bpxtools::{
+    dump::bpxdiff,
+    package::{
+        check::Tested,
+        diff::{Difference, Patch},
+        info::Info,
+        list::{format_bytes, format_size, ListEntry},
+        pack::{self, Packed}
+    }
+};
+
+pub fn metadata(map: &serde_json::Map<String, serde_json::Value>)
+{
+    if map.is_empty() {
+        return;
+    }
+    println!("Metadata:");
+    match serde_json::to_string_pretty(map) {
+        Ok(v) => println!("{}", v),
+        Err(e) => eprintln!("Warning: failed to format metadata ({})", e)
+    }
+}
+
+pub fn info(info: &Info)
+{
+    println!("Architecture: {}", info.architecture.name());
+    println!("Platform: {}", info.platform.name());
+    println!(
+        "Generator: {}{}",
+        info.generator[0] as char, info.generator[1] as char
+    );
+    metadata(&info.metadata);
+}
+
+pub fn tested(tested: &Tested)
+{
+    match &tested.res {
+        Ok(()) => println!("Testing {}... OK", tested.name),
+        Err(e) => println!("Testing {}... FAILED ({})", tested.name, e)
+    }
+}
+
+pub fn dry_run(packed: &[Packed], human: bool, compressed: bool)
+{
+    for v in packed {
+        println!(
+            "Name = '{}', Size = {}",
+            v.name,
+            format_bytes(v.size, human)
+        );
+    }
+    println!(
+        "Total: {} object(s), {}",
+        packed.len(),
+        format_bytes(packed.iter().map(|v| v.size).sum(), human)
+    );
+    let estimate = format_bytes(pack::estimate_size(packed), human);
+    if compressed {
+        println!(
+            "Estimated output size: about {} before compression",
+            estimate
+        );
+    } else {
+        println!("Estimated output size: about {}", estimate);
+    }
+}
+
+//Packages listed with hashes show - for the objects whose digest is not stored
+fn hash(entry: &ListEntry, hashes: bool) -> Option<&str>
+{
+    hashes.then(|| entry.hash.as_deref().unwrap_or("-"))
+}
+
+fn format_sections(sections: &[u32], separator: &str) -> String
+{
+    let sections: Vec<String> = sections.iter().map(|v| v.to_string()).collect();
+    sections.join(separator)
+}
+
+pub fn list_short(entries: &[ListEntry], hashes: bool)
+{
+    println!("Decoding object table:");
+    for v in entries {
+        print!("Name = '{}', Size = {} byte(s)", v.name, v.size);
+        match hash(v, hashes) {
+            Some(hash) => println!(", SHA-256 = {}", hash),
+            None => println!()
+        }
+    }
+}
+
+fn list_total(entries: &[ListEntry], human: bool)
+{
+    let size: u64 = entries.iter().map(|v| v.size).sum();
+    let stored: u64 = entries.iter().map(|v| v.stored).sum();
+    println!(
+        "Total: {} object(s), {}, {} stored",
+        entries.len(),
+        format_bytes(size, human),
+        format_bytes(stored, human)
+    );
+}
+
+pub fn list_long(entries: &[ListEntry], hashes: bool, human: bool)
+{
+    println!(
+        "{:>12} {:>12} {:>7} {:>10}  Name",
+        "Size", "Stored", "Ratio", "Sections"
+    );
+    for v in entries {
+        println!(
+            "{:>12} {:>12} {:>6.1}% {:>10}  {}",
+            format_size(v.size, human),
+            format_size(v.stored, human),
+            v.ratio() * 100.0,
+            format_sections(&v.sections, ","),
+            v.name
+        );
+        if let Some(hash) = hash(v, hashes) {
+            println!("{:>46}SHA-256 {}", "", hash);
+        }
+    }
+    list_total(entries, human);
+}
+
+#[derive(Default)]
+struct Directory<'a>
+{
+    directories: BTreeMap<&'a str, Directory<'a>>,
+    files: Vec<(&'a str, &'a ListEntry)>
+}
+
+impl<'a> Directory<'a>
+{
+    fn insert(&mut self, path: &'a str, entry: &'a ListEntry)
+    {
+        match path.split_once('/') {
+            Some((dir, rest)) => self.directories.entry(dir).or_default().insert(rest, entry),
+            None => self.files.push((path, entry))
+        }
+    }
+
+    fn print(&self, layer: usize, human: bool)
+    {
+        let prefix = "    ".repeat(layer);
+        for (name, dir) in &self.directories {
+            println!("{}{}/", prefix, name);
+            dir.print(layer + 1, human);
+        }
+        for (name, entry) in &self.files {
+            println!("{}{} ({})", prefix, name, format_size(entry.size, human));
+        }
+    }
+}
+
+pub fn list_tree(entries: &[ListEntry], human: bool)
+{
+    let mut root = Directory::default();
+    for v in entries {
+        root.insert(&v.name, v);
+    }
+    root.print(0, human);
+    list_total(entries, human);
+}
+
+pub fn list_json(entries: &[ListEntry], hashes: bool)
+{
+    let objects: Vec<serde_json::Value> = entries
+        .iter()
+        .map(|v| {
+            let mut object = serde_json::json!({
+                "name": v.name,
+                "size": v.size,
+                "stored": v.stored,
+                "ratio": v.ratio(),
+                "sections": v.sections
+            });
+            if let Some(hash) = hash(v, hashes) {
+                object["sha256"] = hash.into();
+            }
+            object
+        })
+        .collect();
+    let json = serde_json::json!({
+        "objects": objects,
+        "total": {
+            "count": entries.len(),
+            "size": entries.iter().map(|v| v.size).sum::<u64>(),
+            "stored": entries.iter().map(|v| v.stored).sum::<u64>()
+        }
+    });
+    println!("{}", json);
+}
+
+fn csv_escape(value: &str) -> String
+{
+    if value.contains(|c| c == ',' || c == '"' || c == '\n') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.into()
+    }
+}
+
+pub fn list_csv(entries: &[ListEntry], hashes: bool)
+{
+    print!("name,size,stored,ratio,sections");
+    if hashes && !entries.is_empty() {
+        print!(",sha256");
+    }
+    println!();
+    for v in entries {
+        print!(
+            "{},{},{},{:.4},{}",
+            csv_escape(&v.name),
+            v.size,
+            v.stored,
+            v.ratio(),
+            format_sections(&v.sections, ";")
+        );
+        if let Some(hash) = hash(v, hashes) {
+            print!(",{}", hash);
+        }
+        println!();
+    }
+}
+
+pub fn package_diff(diffs: &[Difference])
+{
+    for v in diffs {
+        match v {
+            Difference::Target(what, old, new) => println!("{} changed: {} -> {}", what, old, new),
+            Difference::MetadataRemoved(key) => println!("Metadata removed: {}", key),
+            Difference::MetadataAdded(key, value) => {
+                println!("Metadata added: {} = {}", key, value)
+            },
+            Difference::MetadataChanged(key, old, new) => {
+                println!("Metadata changed: {}: {} -> {}", key, old, new)
+            },
+            Difference::Removed(name) => println!("Removed: {}", name),
+            Difference::Added(name) => println!("Added: {}", name),
+            Difference::Renamed(old, new) => println!("Renamed: {} -> {}", old, new),
+            Difference::Changed(name, old_size, new_size, patch) => {
+                println!("Changed: {} ({} -> {} byte(s))", name, old_size, new_size);
+                match patch {
+                    Some(Patch::Text(text)) => print!("{}", text),
+                    Some(Patch::Binary) => {
+                        println!("Binary objects a/{} and b/{} differ", name, name)
+                    },
+                    None => ()
+                }
+            }
+        }
+    }
+    if diffs.is_empty() {
+        println!("Packages are identical");
+    }
+}
+
+pub fn container_diff(diffs: &[bpxdiff::Difference], other: &Path)
+{
+    for v in diffs {
+        match v {
+            bpxdiff::Difference::Field(what, field, a, b) => {
+                println!("{}: {}: {} -> {}", what, field, a, b)
+            },
+            bpxdiff::Difference::TypeExt(offset) => {
+                println!("TypeExt: differs at byte offset {}", offset)
+            },
+            bpxdiff::Difference::MissingSection(index) => {
+                println!("Section #{}: missing in {}", index, other.display())
+            },
+            bpxdiff::Difference::ExtraSection(index) => {
+                println!("Section #{}: only present in {}", index, other.display())
+            },
+            bpxdiff::Difference::SectionData(index, offset) => {
+                println!("Section #{}: data differs at byte offset {}", index, offset)
+            }
+        }
+    }
+    if diffs.is_empty() {
+        println!("Files are identical");
+    }
+}
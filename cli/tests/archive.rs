@@ -0,0 +1,157 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fs::{create_dir_all, read_to_string, remove_dir_all, remove_file, write, File};
+
+use predicates::prelude::*;
+use serial_test::serial;
+
+mod common;
+
+use common::bpx;
+
+const EXPECTED_OUTPUT: &str = "Decoding object table:
+Name = 'archive_test/a.txt', Size = 5 byte(s)
+Name = 'archive_test/sub/b.txt', Size = 6 byte(s)
+";
+
+fn round_trip(archive: &str)
+{
+    create_dir_all("archive_test/sub").unwrap();
+    write("archive_test/a.txt", "hello").unwrap();
+    write("archive_test/sub/b.txt", "world!").unwrap();
+    bpx(&["-f", "archive.bpx", "pack", "archive_test"])
+        .success()
+        .stdout("")
+        .stderr("");
+    remove_dir_all("archive_test").unwrap();
+    bpx(&["-f", "archive.bpx", "export", archive])
+        .success()
+        .stdout("")
+        .stderr("");
+    remove_file("archive.bpx").unwrap();
+    bpx(&["-f", "archive.bpx", "import", archive])
+        .success()
+        .stdout("")
+        .stderr("");
+    bpx(&["-f", "archive.bpx", "list"])
+        .success()
+        .stdout(EXPECTED_OUTPUT)
+        .stderr("");
+    bpx(&["-f", "archive.bpx", "unpack"])
+        .success()
+        .stdout("")
+        .stderr("");
+    assert_eq!(read_to_string("archive_test/a.txt").unwrap(), "hello");
+    assert_eq!(read_to_string("archive_test/sub/b.txt").unwrap(), "world!");
+    remove_dir_all("archive_test").unwrap();
+    remove_file("archive.bpx").unwrap();
+    remove_file(archive).unwrap();
+}
+
+#[test]
+#[serial]
+fn tar_round_trip()
+{
+    round_trip("archive.tar");
+}
+
+#[test]
+#[serial]
+fn zip_round_trip()
+{
+    round_trip("archive.zip");
+}
+
+#[test]
+#[serial]
+fn unsupported_format()
+{
+    bpx(&["-f", "archive.bpx", "import", "archive.rar"])
+        .failure()
+        .stderr(predicate::str::contains("unsupported archive format"));
+    bpx(&["-f", "tests/test.bpx", "export", "archive.7z"])
+        .failure()
+        .stderr(predicate::str::contains("Unsupported archive format"));
+}
+
+fn create_linked_tar(archive: &str)
+{
+    let mut builder = tar::Builder::new(File::create(archive).unwrap());
+    let mut header = tar::Header::new_gnu();
+    header.set_size(5);
+    header.set_mode(0o644);
+    builder
+        .append_data(&mut header, "links_test/a.txt", "hello".as_bytes())
+        .unwrap();
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Link);
+    header.set_size(0);
+    header.set_mode(0o644);
+    builder
+        .append_link(&mut header, "links_test/b.txt", "links_test/a.txt")
+        .unwrap();
+    builder.finish().unwrap();
+}
+
+#[test]
+#[serial]
+fn tar_hard_links()
+{
+    create_linked_tar("links.tar");
+    bpx(&["-f", "links.bpx", "import", "links.tar"])
+        .success()
+        .stdout("")
+        .stderr("");
+    //Hard links become copies of the entry they point to, which survive an export and import
+    bpx(&["-f", "links.bpx", "export", "links.tar"])
+        .success()
+        .stdout("")
+        .stderr("");
+    bpx(&["-f", "links.bpx", "import", "links.tar"])
+        .success()
+        .stdout("")
+        .stderr("");
+    bpx(&["-f", "links.bpx", "list"])
+        .success()
+        .stdout(
+            "Decoding object table:
+Name = 'links_test/a.txt', Size = 5 byte(s)
+Name = 'links_test/b.txt', Size = 5 byte(s)
+"
+        )
+        .stderr("");
+    bpx(&["-f", "links.bpx", "unpack"])
+        .success()
+        .stdout("")
+        .stderr("");
+    assert_eq!(read_to_string("links_test/b.txt").unwrap(), "hello");
+    remove_dir_all("links_test").unwrap();
+    remove_file("links.bpx").unwrap();
+    remove_file("links.tar").unwrap();
+}
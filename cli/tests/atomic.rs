@@ -0,0 +1,78 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//Reading /proc/self/mem at offset 0 always fails which makes packing fail after the output has
+//been created
+#![cfg(target_os = "linux")]
+
+use std::fs::{read_dir, remove_file};
+
+use serial_test::serial;
+
+mod common;
+
+const EXPECTED_OUTPUT: &str = "Decoding object table:
+Name = 'LICENSE.txt', Size = 1518 byte(s)
+";
+
+fn bpx(args: &[&str]) -> assert_cmd::assert::Assert
+{
+    common::bpx_file("atomic.bpx", args)
+}
+
+fn temp_files() -> usize
+{
+    read_dir(".")
+        .unwrap()
+        .filter(|v| {
+            v.as_ref()
+                .unwrap()
+                .file_name()
+                .to_string_lossy()
+                .starts_with(".tmp")
+        })
+        .count()
+}
+
+#[test]
+#[serial]
+fn failed_pack_keeps_previous_package()
+{
+    bpx(&["pack", "../LICENSE.txt"])
+        .success()
+        .stdout("")
+        .stderr("");
+    let temp_count = temp_files();
+    bpx(&["pack", "../LICENSE.txt", "/proc/self/mem=mem"]).failure();
+    assert_eq!(temp_files(), temp_count);
+    bpx(&["list"]).success().stdout(EXPECTED_OUTPUT).stderr("");
+    bpx(&["update", "--add", "/proc/self/mem=mem"]).failure();
+    assert_eq!(temp_files(), temp_count);
+    bpx(&["list"]).success().stdout(EXPECTED_OUTPUT).stderr("");
+    remove_file("atomic.bpx").unwrap();
+}
@@ -0,0 +1,235 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+#![cfg(unix)]
+
+use std::{
+    fs::{
+        create_dir_all,
+        read_link,
+        remove_dir_all,
+        remove_file,
+        set_permissions,
+        write,
+        File,
+        Permissions
+    },
+    os::unix::fs::{symlink, PermissionsExt},
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH}
+};
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use serial_test::serial;
+
+fn mtime() -> SystemTime
+{
+    UNIX_EPOCH + Duration::from_secs(1_000_000_000)
+}
+
+fn mode(path: &str) -> u32
+{
+    std::fs::metadata(path).unwrap().permissions().mode() & 0o777
+}
+
+fn pack_unpack(pack_args: &[&str], unpack_args: &[&str])
+{
+    create_dir_all("attributes_test").unwrap();
+    write("attributes_test/tool.sh", "#!/bin/sh\necho test\n").unwrap();
+    set_permissions("attributes_test/tool.sh", Permissions::from_mode(0o750)).unwrap();
+    File::options()
+        .write(true)
+        .open("attributes_test/tool.sh")
+        .unwrap()
+        .set_modified(mtime())
+        .unwrap();
+    symlink("tool.sh", "attributes_test/link").unwrap();
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "attributes.bpx", "pack", "attributes_test"])
+        .args(pack_args)
+        .assert();
+    assert.success().stdout("").stderr("");
+    remove_dir_all("attributes_test").unwrap();
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "attributes.bpx", "unpack"])
+        .args(unpack_args)
+        .assert();
+    assert.success().stdout("").stderr("");
+}
+
+#[test]
+#[serial]
+fn preserve()
+{
+    pack_unpack(&[], &[]);
+    assert_eq!(mode("attributes_test/tool.sh"), 0o750);
+    assert_eq!(
+        std::fs::metadata("attributes_test/tool.sh")
+            .unwrap()
+            .modified()
+            .unwrap(),
+        mtime()
+    );
+    assert_eq!(
+        read_link("attributes_test/link").unwrap(),
+        Path::new("tool.sh")
+    );
+    remove_dir_all("attributes_test").unwrap();
+    remove_file("attributes.bpx").unwrap();
+}
+
+#[test]
+#[serial]
+fn no_preserve()
+{
+    pack_unpack(&["--no-preserve"], &["--no-preserve"]);
+    assert_eq!(mode("attributes_test/tool.sh") & 0o111, 0);
+    assert!(read_link("attributes_test/link").is_err());
+    remove_dir_all("attributes_test").unwrap();
+    remove_file("attributes.bpx").unwrap();
+}
+
+#[test]
+#[serial]
+fn unsafe_links()
+{
+    create_dir_all("attributes_test").unwrap();
+    symlink("../..", "attributes_test/escape").unwrap();
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&[
+            "-f",
+            "attributes.bpx",
+            "pack",
+            "attributes_test",
+            "../LICENSE.txt=attributes_test/escape/pwned.txt"
+        ])
+        .assert();
+    assert.success().stdout("").stderr("");
+    remove_dir_all("attributes_test").unwrap();
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "attributes.bpx", "unpack"])
+        .assert();
+    assert
+        .failure()
+        .stdout("")
+        .stderr("Refusing to unpack symbolic link 'attributes_test/escape' pointing outside of the target directory (../..)\n");
+    assert!(!Path::new("../pwned.txt").exists());
+    assert!(!Path::new("attributes_test").exists());
+
+    create_dir_all("attributes_test").unwrap();
+    symlink("/tmp", "attributes_test/absolute").unwrap();
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "attributes.bpx", "pack", "attributes_test"])
+        .assert();
+    assert.success().stdout("").stderr("");
+    remove_dir_all("attributes_test").unwrap();
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "attributes.bpx", "unpack"])
+        .assert();
+    assert.failure().stdout("").stderr(predicate::str::contains(
+        "'attributes_test/absolute' pointing outside of the target directory"
+    ));
+
+    //Each link stays in the target directory on its own but the second one goes through the first
+    create_dir_all("attributes_test/d").unwrap();
+    symlink("../..", "attributes_test/d/up").unwrap();
+    symlink("up/..", "attributes_test/d/e").unwrap();
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "attributes.bpx", "pack", "attributes_test"])
+        .assert();
+    assert.success().stdout("").stderr("");
+    remove_dir_all("attributes_test").unwrap();
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "attributes.bpx", "unpack"])
+        .assert();
+    assert
+        .failure()
+        .stdout("")
+        .stderr("Refusing to unpack symbolic link 'attributes_test/d/e' pointing outside of the target directory (up/..)\n");
+    assert!(!Path::new("attributes_test").exists());
+    remove_file("attributes.bpx").unwrap();
+}
+
+#[test]
+#[serial]
+fn existing_links_are_not_followed()
+{
+    create_dir_all("attributes_test").unwrap();
+    write("attributes_test/data.txt", "data\n").unwrap();
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "attributes.bpx", "pack", "attributes_test"])
+        .assert();
+    assert.success().stdout("").stderr("");
+    remove_dir_all("attributes_test").unwrap();
+    create_dir_all("attributes_outside").unwrap();
+    symlink("attributes_outside", "attributes_test").unwrap();
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "attributes.bpx", "unpack"])
+        .assert();
+    assert.failure().stdout("").stderr(
+        "Refusing to unpack object 'attributes_test/data.txt' outside of the target directory\n"
+    );
+    assert!(!Path::new("attributes_outside/data.txt").exists());
+    remove_file("attributes_test").unwrap();
+    remove_dir_all("attributes_outside").unwrap();
+    remove_file("attributes.bpx").unwrap();
+}
+
+#[test]
+#[serial]
+fn link_loop()
+{
+    create_dir_all("attributes_test/dir").unwrap();
+    symlink("..", "attributes_test/dir/self").unwrap();
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&[
+            "-f",
+            "attributes.bpx",
+            "pack",
+            "--no-preserve",
+            "attributes_test"
+        ])
+        .assert();
+    assert.failure().stdout("").stderr(predicate::str::contains(
+        "symbolic link loop: attributes_test/dir/self points to one of its parent directories"
+    ));
+    assert!(!Path::new("attributes.bpx").exists());
+    remove_dir_all("attributes_test").unwrap();
+}
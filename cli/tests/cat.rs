@@ -26,46 +26,42 @@
 // NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
 // SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use assert_cmd::Command;
-
-const EXPECTED_OUTPUT: &str = "====> BPX Main Header <====
-Type: P
-Version: 1
-File size: 1632
-Number of sections: 2
-====> End <====
-
-====> BPX Section Header Table <====
-Section #0:
-	Type: 255
-	Size (after compression): 15
-	Size: 15
-	Flags:  CheckWeak
-Section #1:
-	Type: 1
-	Size (after compression): 1529
-	Size: 1529
-	Flags:  CheckWeak
-====> End <====
+use std::fs::{read, remove_file};
 
-";
+use assert_cmd::Command;
+use predicates::prelude::*;
+use serial_test::serial;
 
 #[test]
-fn dump_sht_1()
+#[serial]
+fn cat()
 {
-    let assert = Command::cargo_bin("bpxdump")
+    let assert = Command::cargo_bin("bpx")
         .unwrap()
-        .args(&["-f", "tests/test.bpx", "-s"])
+        .args(&[
+            "-f",
+            "cat.bpx",
+            "pack",
+            "../LICENSE.txt",
+            "tests/metadata.json"
+        ])
         .assert();
-    assert.success().stdout(EXPECTED_OUTPUT).stderr("");
-}
-
-#[test]
-fn dump_sht_2()
-{
-    let assert = Command::cargo_bin("bpxdump")
+    assert.success().stdout("").stderr("");
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "cat.bpx", "cat", "LICENSE.txt"])
+        .assert();
+    assert
+        .success()
+        .stdout(read("../LICENSE.txt").unwrap())
+        .stderr("");
+    let assert = Command::cargo_bin("bpx")
         .unwrap()
-        .args(&["-f", "tests/test.bpx", "--sht"])
+        .args(&["-f", "cat.bpx", "cat", "missing.txt"])
         .assert();
-    assert.success().stdout(EXPECTED_OUTPUT).stderr("");
+    assert
+        .failure()
+        .stdout("")
+        .stderr("Could not find object named 'missing.txt'\n");
+    remove_file("cat.bpx").unwrap();
 }
@@ -0,0 +1,107 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    convert::TryInto,
+    fs::{remove_file, write}
+};
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use serial_test::serial;
+
+//Size of the BPX main header and of a section header
+const MAIN_HEADER_SIZE: usize = 40;
+const SECTION_HEADER_SIZE: usize = 24;
+const SECTION_TYPE_DATA: u8 = 0x1;
+
+fn read_u32(data: &[u8], offset: usize) -> u32
+{
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+//Returns the offset and stored size of the first data section
+fn data_section(data: &[u8]) -> (usize, usize)
+{
+    let count = read_u32(data, 16) as usize;
+    (0..count)
+        .map(|i| MAIN_HEADER_SIZE + i * SECTION_HEADER_SIZE)
+        .find(|header| data[header + 20] == SECTION_TYPE_DATA)
+        .map(|header| {
+            let pointer = u64::from_le_bytes(data[header..header + 8].try_into().unwrap());
+            (pointer as usize, read_u32(data, header + 8) as usize)
+        })
+        .unwrap()
+}
+
+#[test]
+#[serial]
+fn check_valid()
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "check.bpx", "pack", "../LICENSE.txt"])
+        .assert();
+    assert.success().stdout("").stderr("");
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "check.bpx", "verify"])
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("Testing LICENSE.txt... OK\n"))
+        .stdout(predicate::str::ends_with(
+            "No errors detected in check.bpx\n"
+        ))
+        .stderr("");
+    remove_file("check.bpx").unwrap();
+}
+
+#[test]
+#[serial]
+fn check_corrupted()
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "check.bpx", "pack", "../LICENSE.txt"])
+        .assert();
+    assert.success().stdout("").stderr("");
+    let mut data = std::fs::read("check.bpx").unwrap();
+    let (offset, size) = data_section(&data);
+    data[offset + size / 2] ^= 0xFF;
+    write("check.bpx", data).unwrap();
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "check.bpx", "verify"])
+        .assert();
+    assert
+        .failure()
+        .stdout(predicate::str::contains("Testing LICENSE.txt... FAILED"))
+        .stderr(predicate::str::contains("failed verification"));
+    remove_file("check.bpx").unwrap();
+}
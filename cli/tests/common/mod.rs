@@ -26,24 +26,23 @@
 // NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
 // SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{fs::File, path::Path};
+//Each test crate only uses some of the helpers
+#![allow(dead_code)]
 
-use bpx::package::{utils::pack_file, Builder, Package};
-use clap::ArgMatches;
+use assert_cmd::{assert::Assert, Command};
 
-use crate::error::PackError;
-
-pub fn run(file: &Path, matches: &ArgMatches) -> Result<(), PackError>
+/// Runs the bpx binary with the given arguments.
+pub fn bpx(args: &[&str]) -> Assert
 {
-    let mut encoder = Package::create(File::create(file)?, Builder::new().with_type(*b"BD"))?;
-    let files: Vec<&str> = matches.values_of("files").unwrap().collect();
+    Command::cargo_bin("bpx").unwrap().args(args).assert()
+}
 
-    for v in files {
-        if matches.is_present("verbose") {
-            println!("Packing {}...", v);
-        }
-        pack_file(&mut encoder, Path::new(v))?;
-    }
-    encoder.save()?;
-    Ok(())
+/// Runs the bpx binary on the given BPX file.
+pub fn bpx_file(file: &str, args: &[&str]) -> Assert
+{
+    Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", file])
+        .args(args)
+        .assert()
 }
@@ -36,16 +36,16 @@ use serial_test::serial;
 #[serial]
 fn pack_unpack()
 {
-    let assert = Command::cargo_bin("bpxp")
+    let assert = Command::cargo_bin("bpx")
         .unwrap()
-        .args(&["-f", "test.bpx", "-p", "../target/debug/bpxdump"])
+        .args(&["-f", "test.bpx", "pack", "../target/debug/bpx"])
         .assert();
     assert.success().stdout("").stderr("");
-    let assert = Command::cargo_bin("bpxp")
+    let assert = Command::cargo_bin("bpx")
         .unwrap()
-        .args(&["-f", "test.bpx", "-u"])
+        .args(&["-f", "test.bpx", "unpack"])
         .assert();
     assert.success().stdout("").stderr("");
-    assert!(diff("bpxdump", "../target/debug/bpxdump"));
-    remove_file("bpxdump").unwrap();
+    assert!(diff("bpx", "../target/debug/bpx"));
+    remove_file("bpx").unwrap();
 }
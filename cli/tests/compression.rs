@@ -0,0 +1,124 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fs::remove_file;
+
+use assert_cmd::Command;
+use file_diff::diff;
+use predicates::prelude::*;
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn pack_zlib_crc32()
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&[
+            "-f",
+            "compression.bpx",
+            "pack",
+            "--compression",
+            "zlib",
+            "--threshold",
+            "0",
+            "--checksum",
+            "crc32",
+            "../LICENSE.txt"
+        ])
+        .assert();
+    assert.success().stdout("").stderr("");
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "compression.bpx", "sht"])
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("CompressZlib | CheckCrc32"))
+        .stdout(predicate::str::contains("CheckWeak").not())
+        .stderr("");
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "compression.bpx", "unpack"])
+        .assert();
+    assert.success().stdout("").stderr("");
+    assert!(diff("LICENSE.txt", "../LICENSE.txt"));
+    remove_file("LICENSE.txt").unwrap();
+    remove_file("compression.bpx").unwrap();
+}
+
+#[test]
+#[serial]
+fn pack_no_checksum()
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&[
+            "-f",
+            "compression.bpx",
+            "pack",
+            "--compression",
+            "none",
+            "--checksum",
+            "none",
+            "../LICENSE.txt"
+        ])
+        .assert();
+    assert.success().stdout("").stderr("");
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "compression.bpx", "sht"])
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("Flags:  CheckNone"))
+        .stdout(predicate::str::contains("Compress").not())
+        .stderr("");
+    remove_file("compression.bpx").unwrap();
+}
+
+#[test]
+#[serial]
+fn invalid_level()
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&[
+            "-f",
+            "compression.bpx",
+            "pack",
+            "--level",
+            "max",
+            "../LICENSE.txt"
+        ])
+        .assert();
+    assert
+        .failure()
+        .stdout("")
+        .stderr(predicate::str::contains("could not parse level"));
+}
@@ -0,0 +1,111 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fs::{create_dir_all, remove_dir_all, remove_file, write};
+
+use predicates::prelude::*;
+use serial_test::serial;
+
+mod common;
+
+use common::bpx;
+
+const EXPECTED_OUTPUT: &str = "Architecture changed: Any -> x86_64
+Metadata removed: build
+Metadata removed: version
+Removed: gone.txt
+Added: extra.txt
+Renamed: old.txt -> new.txt
+Changed: text.txt (12 -> 20 byte(s))
+";
+
+fn create_packages()
+{
+    create_dir_all("diff_test").unwrap();
+    write("diff_test/keep.txt", "same\n").unwrap();
+    write("diff_test/moved.txt", "moved content\n").unwrap();
+    write("diff_test/gone.txt", "bye\n").unwrap();
+    write("diff_test/extra.txt", "hi\n").unwrap();
+    write("diff_test/text1.txt", "line1\nline2\n").unwrap();
+    write("diff_test/text2.txt", "line1\nline2 changed\n").unwrap();
+    bpx(&[
+        "-f",
+        "diff1.bpx",
+        "pack",
+        "--metadata",
+        "tests/metadata.json",
+        "diff_test/keep.txt=keep.txt",
+        "diff_test/moved.txt=old.txt",
+        "diff_test/gone.txt=gone.txt",
+        "diff_test/text1.txt=text.txt"
+    ])
+    .success();
+    bpx(&[
+        "-f",
+        "diff2.bpx",
+        "pack",
+        "--arch",
+        "x86_64",
+        "diff_test/keep.txt=keep.txt",
+        "diff_test/moved.txt=new.txt",
+        "diff_test/extra.txt=extra.txt",
+        "diff_test/text2.txt=text.txt"
+    ])
+    .success();
+    remove_dir_all("diff_test").unwrap();
+}
+
+fn cleanup()
+{
+    remove_file("diff1.bpx").unwrap();
+    remove_file("diff2.bpx").unwrap();
+}
+
+#[test]
+#[serial]
+fn diff()
+{
+    create_packages();
+    bpx(&["-f", "diff1.bpx", "diff", "diff2.bpx"])
+        .code(1)
+        .stdout(EXPECTED_OUTPUT)
+        .stderr("");
+    bpx(&["-f", "diff1.bpx", "diff", "-U", "diff2.bpx"])
+        .code(1)
+        .stdout(
+            predicate::str::contains("--- a/text.txt\n+++ b/text.txt\n")
+                .and(predicate::str::contains(" line1\n-line2\n+line2 changed\n"))
+        )
+        .stderr("");
+    bpx(&["-f", "diff1.bpx", "diff", "diff1.bpx"])
+        .success()
+        .stdout("Packages are identical\n")
+        .stderr("");
+    bpx(&["-f", "diff1.bpx", "diff", "missing.bpx"]).code(2);
+    cleanup();
+}
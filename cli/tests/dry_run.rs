@@ -0,0 +1,114 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::path::Path;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use serial_test::serial;
+
+mod common;
+
+fn bpx(args: &[&str]) -> assert_cmd::assert::Assert
+{
+    common::bpx_file("dry_run.bpx", &[&["pack", "--dry-run"], args].concat())
+}
+
+#[test]
+#[serial]
+fn dry_run()
+{
+    bpx(&[
+        "--compression",
+        "none",
+        "../LICENSE.txt",
+        "tests/metadata.json"
+    ])
+    .success()
+    .stdout(
+        "Name = 'LICENSE.txt', Size = 1518 byte(s)
+Name = 'metadata.json', Size = 46 byte(s)
+Total: 2 object(s), 1564 byte(s)
+Estimated output size: about 1670 byte(s)
+"
+    )
+    .stderr("");
+    assert!(!Path::new("dry_run.bpx").exists());
+}
+
+#[test]
+#[serial]
+fn dry_run_collision()
+{
+    bpx(&["../LICENSE.txt", "tests/metadata.json=LICENSE.txt"])
+        .failure()
+        .stderr(
+            predicate::str::contains("Name collision: 'LICENSE.txt'")
+                .and(predicate::str::contains("1 problem(s) found"))
+        );
+    assert!(!Path::new("dry_run.bpx").exists());
+}
+
+#[test]
+#[serial]
+fn dry_run_reports_all_problems()
+{
+    bpx(&[
+        "missing.txt",
+        "../LICENSE.txt",
+        "tests/metadata.json=LICENSE.txt",
+        "tests/missing.json"
+    ])
+    .failure()
+    .stderr(
+        predicate::str::contains("Cannot pack missing.txt")
+            .and(predicate::str::contains("Cannot pack tests/missing.json"))
+            .and(predicate::str::contains("Name collision: 'LICENSE.txt'"))
+            .and(predicate::str::contains("3 problem(s) found"))
+    );
+    assert!(!Path::new("dry_run.bpx").exists());
+}
+
+#[test]
+#[serial]
+fn pack_collision()
+{
+    Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&[
+            "-f",
+            "dry_run.bpx",
+            "pack",
+            "../LICENSE.txt",
+            "tests/metadata.json=LICENSE.txt"
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Name collision: 'LICENSE.txt'"));
+    assert!(!Path::new("dry_run.bpx").exists());
+}
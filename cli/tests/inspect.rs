@@ -0,0 +1,229 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+const EXPECTED_INFO: &str = "====> BPX Main Header <====
+Type: P
+Version: 1
+File size: 1632
+Number of sections: 2
+====> End <====
+
+====> BPX TypeExt <====
+Architecture: Any
+Platform: Any
+Generator: BD
+====> End <====
+
+";
+
+const EXPECTED_INFO_HEX: &str = "====> BPX Main Header <====
+Type: P
+Version: 1
+File size: 1632
+Number of sections: 2
+====> End <====
+
+====> BPX TypeExt <====
+04 04 42 44 00 00 00 00 00 00 00 00 00 00 00 00 
+====> End <====
+
+";
+
+const EXPECTED_SHT: &str = "====> BPX Section Header Table <====
+Section #0:
+	Type: 255
+	Size (after compression): 15
+	Size: 15
+	Flags:  CheckWeak
+Section #1:
+	Type: 1
+	Size (after compression): 1529
+	Size: 1529
+	Flags:  CheckWeak
+====> End <====
+
+";
+
+const EXPECTED_HEX: &str = "4C 49 43 45 4E 53 45 5F 4C 46 2E 74 78 74 00 \n";
+
+const EXPECTED_ERROR_RAW: &str =
+    "Outputing binary data to standard output can mess-up your terminal, please use --force if you're sure to continue
+";
+
+#[test]
+fn info()
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "tests/test.bpx", "info"])
+        .assert();
+    assert.success().stdout(EXPECTED_INFO).stderr("");
+}
+
+#[test]
+fn info_hex()
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "tests/test.bpx", "info", "--hex"])
+        .assert();
+    assert.success().stdout(EXPECTED_INFO_HEX).stderr("");
+}
+
+#[test]
+fn info_json()
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["info", "-f", "tests/test.bpx", "--format", "json"])
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("\"generator\":\"BD\""))
+        .stderr("");
+}
+
+#[test]
+fn sht()
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "tests/test.bpx", "sht"])
+        .assert();
+    assert.success().stdout(EXPECTED_SHT).stderr("");
+}
+
+#[test]
+fn dump_hex()
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "tests/test.bpx", "dump", "0", "--hex"])
+        .assert();
+    assert.success().stdout(EXPECTED_HEX).stderr("");
+}
+
+#[test]
+fn dump_raw()
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "tests/test.bpx", "dump", "0"])
+        .assert();
+    assert.failure().stdout("").stderr(EXPECTED_ERROR_RAW);
+}
+
+#[test]
+fn diff_identical()
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&[
+            "-f",
+            "tests/test.bpx",
+            "diff",
+            "--container",
+            "tests/test.bpx"
+        ])
+        .assert();
+    assert.success().stdout("Files are identical\n").stderr("");
+}
+
+#[test]
+fn diff_error()
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "tests/test.bpx", "diff", "doesnotexist.bpx"])
+        .assert();
+    assert
+        .code(2)
+        .stdout("")
+        .stderr(predicate::str::contains("IO error:"));
+}
+
+#[test]
+fn verify()
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "tests/test.bpx", "verify"])
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("No errors detected"));
+}
+
+#[test]
+fn missing_file()
+{
+    let assert = Command::cargo_bin("bpx").unwrap().args(&["info"]).assert();
+    assert
+        .code(2)
+        .stdout("")
+        .stderr(predicate::str::contains("Usage error:"));
+}
+
+#[test]
+fn unsupported_format()
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "tests/test.bpx", "--format", "csv", "info"])
+        .assert();
+    assert
+        .code(2)
+        .stdout("")
+        .stderr(predicate::str::contains("Usage error:"));
+}
+
+#[test]
+fn unknown_option()
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "tests/test.bpx", "sht", "--nope"])
+        .assert();
+    assert.code(2).stdout("");
+}
+
+#[test]
+fn error()
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "doesnotexist.bpx", "info"])
+        .assert();
+    assert
+        .code(1)
+        .stdout("")
+        .stderr(predicate::str::contains("IO error:"));
+}
@@ -0,0 +1,108 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    fs::{create_dir_all, read_to_string, remove_dir_all, remove_file, write},
+    path::Path
+};
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use serial_test::serial;
+
+fn pack(file: &str)
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .env("SOURCE_DATE_EPOCH", "1000")
+        .args(&["-f", file, "pack", "--hashes", "jobs_test"])
+        .assert();
+    assert.success().stdout("").stderr("");
+}
+
+#[test]
+#[serial]
+fn jobs_rejected_when_packing()
+{
+    create_dir_all("jobs_test").unwrap();
+    write("jobs_test/0.txt", "data 0").unwrap();
+    Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "jobs.bpx", "pack", "-j", "4", "jobs_test"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("wasn't expected"));
+    Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "jobs.bpx", "unpack", "-j", "many"])
+        .assert()
+        .failure();
+    remove_dir_all("jobs_test").unwrap();
+    assert!(!Path::new("jobs.bpx").exists());
+}
+
+#[test]
+#[serial]
+fn parallel_unpack_and_test()
+{
+    create_dir_all("jobs_test").unwrap();
+    let mut expected = String::new();
+    let mut unpacked = String::new();
+    for i in 0..8 {
+        write(format!("jobs_test/{}.txt", i), format!("data {}", i)).unwrap();
+        expected += &format!("Testing jobs_test/{}.txt... OK\n", i);
+        unpacked += &format!(
+            "Unpacking object name jobs_test/{}.txt with 6 byte(s)...\n",
+            i
+        );
+    }
+    pack("jobs.bpx");
+    remove_dir_all("jobs_test").unwrap();
+    Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "jobs.bpx", "-v", "unpack", "-j", "4"])
+        .assert()
+        .success()
+        .stdout(unpacked)
+        .stderr("");
+    for i in 0..8 {
+        assert_eq!(
+            read_to_string(format!("jobs_test/{}.txt", i)).unwrap(),
+            format!("data {}", i)
+        );
+    }
+    Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "jobs.bpx", "verify", "-j", "3"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(expected))
+        .stderr("");
+    remove_dir_all("jobs_test").unwrap();
+    remove_file("jobs.bpx").unwrap();
+}
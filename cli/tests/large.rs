@@ -0,0 +1,89 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fs::{create_dir_all, read_to_string, remove_dir_all, remove_file, write};
+
+use predicates::prelude::*;
+use serial_test::serial;
+
+mod common;
+
+//More objects than a single BPXSD object can hold keys
+const FILE_COUNT: usize = 300;
+
+fn bpx(args: &[&str]) -> assert_cmd::assert::Assert
+{
+    common::bpx_file("large.bpx", args)
+}
+
+fn pack_unpack(pack_args: &[&str])
+{
+    create_dir_all("large_test").unwrap();
+    for i in 0..FILE_COUNT {
+        write(format!("large_test/{}.txt", i), format!("file {}\n", i)).unwrap();
+    }
+    let mut args = vec!["pack", "large_test"];
+    args.extend_from_slice(pack_args);
+    bpx(&args).success().stdout("").stderr("");
+    remove_dir_all("large_test").unwrap();
+    bpx(&["verify"]).success().stderr("");
+    if pack_args.contains(&"--hashes") {
+        bpx(&["list", "--hashes"])
+            .success()
+            .stdout(predicate::str::contains("SHA-256 = -").not())
+            .stderr("");
+    }
+    bpx(&["unpack"]).success().stdout("").stderr("");
+    for i in 0..FILE_COUNT {
+        let data = read_to_string(format!("large_test/{}.txt", i)).unwrap();
+        assert_eq!(data, format!("file {}\n", i));
+    }
+    remove_dir_all("large_test").unwrap();
+    remove_file("large.bpx").unwrap();
+}
+
+#[test]
+#[serial]
+fn large_default()
+{
+    pack_unpack(&[]);
+}
+
+#[test]
+#[serial]
+fn large_hashes()
+{
+    pack_unpack(&["--hashes"]);
+}
+
+#[test]
+#[serial]
+fn large_hashes_no_preserve()
+{
+    pack_unpack(&["--hashes", "--no-preserve"]);
+}
@@ -0,0 +1,91 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fs::remove_file;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use serial_test::serial;
+
+fn list(args: &[&str]) -> assert_cmd::assert::Assert
+{
+    Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "list.bpx", "list"])
+        .args(args)
+        .assert()
+}
+
+#[test]
+#[serial]
+fn list_modes()
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&[
+            "-f",
+            "list.bpx",
+            "pack",
+            "--prefix",
+            "docs",
+            "../LICENSE.txt",
+            "tests/metadata.json"
+        ])
+        .assert();
+    assert.success().stdout("").stderr("");
+    list(&["--long"])
+        .success()
+        .stdout(predicate::str::starts_with(
+            "        Size       Stored   Ratio   Sections  Name\n"
+        ))
+        .stdout(predicate::str::contains("docs/LICENSE.txt"))
+        .stdout(predicate::str::contains("Total: 2 object(s), 1564 byte(s)"))
+        .stderr("");
+    list(&["--tree", "--sort", "size", "--human"])
+        .success()
+        .stdout(predicate::str::starts_with(
+            "docs/
+    LICENSE.txt (1.5 KiB)
+    metadata.json (46)
+Total: 2 object(s), 1.5 KiB, "
+        ))
+        .stderr("");
+    list(&["--format", "json"])
+        .success()
+        .stdout(predicate::str::contains("\"name\":\"docs/metadata.json\""))
+        .stdout(predicate::str::contains("\"count\":2"))
+        .stderr("");
+    list(&["--format", "csv", "--sort", "name"])
+        .success()
+        .stdout(predicate::str::starts_with(
+            "name,size,stored,ratio,sections
+docs/LICENSE.txt,1518,"
+        ))
+        .stderr("");
+    remove_file("list.bpx").unwrap();
+}
@@ -0,0 +1,174 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fs::{create_dir_all, remove_dir_all, remove_file, write};
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use serial_test::serial;
+
+const EXPECTED_OUTPUT: &str = "Decoding object table:
+Name = 'LICENSE.txt', Size = 1518 byte(s)
+Name = 'data/metadata.json', Size = 46 byte(s)
+";
+
+fn pack(args: &[&str]) -> assert_cmd::assert::Assert
+{
+    Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "manifest.bpx", "pack"])
+        .args(args)
+        .assert()
+}
+
+fn list()
+{
+    Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "manifest.bpx", "list"])
+        .assert()
+        .success()
+        .stdout(EXPECTED_OUTPUT)
+        .stderr("");
+}
+
+#[test]
+#[serial]
+fn file_list()
+{
+    write(
+        "manifest.txt",
+        "../LICENSE.txt\ntests/metadata.json=data/metadata.json\n"
+    )
+    .unwrap();
+    pack(&["-T", "manifest.txt"])
+        .success()
+        .stdout("")
+        .stderr("");
+    list();
+    write(
+        "manifest.txt",
+        "../LICENSE.txt\0tests/metadata.json=data/metadata.json\0"
+    )
+    .unwrap();
+    pack(&["-T", "manifest.txt"])
+        .success()
+        .stdout("")
+        .stderr("");
+    list();
+    remove_file("manifest.txt").unwrap();
+    remove_file("manifest.bpx").unwrap();
+}
+
+#[test]
+#[serial]
+fn stdin_list()
+{
+    Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "manifest.bpx", "pack", "-T", "-"])
+        .write_stdin("../LICENSE.txt\ntests/metadata.json=data/metadata.json\n")
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+    list();
+    remove_file("manifest.bpx").unwrap();
+}
+
+#[test]
+#[serial]
+fn toml_and_json_manifests()
+{
+    write(
+        "manifest.toml",
+        "[[objects]]
+source = \"../LICENSE.txt\"
+
+[[objects]]
+source = \"tests/metadata.json\"
+name = \"data/metadata.json\"
+"
+    )
+    .unwrap();
+    pack(&["-T", "manifest.toml"])
+        .success()
+        .stdout("")
+        .stderr("");
+    list();
+    write(
+        "manifest.json",
+        r#"{"objects": [
+            {"source": "../LICENSE.txt"},
+            {"source": "tests/metadata.json", "name": "data/metadata.json"}
+        ]}"#
+    )
+    .unwrap();
+    pack(&["-T", "manifest.json"])
+        .success()
+        .stdout("")
+        .stderr("");
+    list();
+    write(
+        "manifest.json",
+        r#"{"objects": [{"source": "../LICENSE.txt", "compression": "xz"}]}"#
+    )
+    .unwrap();
+    pack(&["-T", "manifest.json"]).failure().stderr(
+        predicate::str::contains("manifest.json sets the compression of ../LICENSE.txt")
+            .and(predicate::str::contains("(use --compression instead)"))
+    );
+    remove_file("manifest.toml").unwrap();
+    remove_file("manifest.json").unwrap();
+    remove_file("manifest.bpx").unwrap();
+}
+
+#[test]
+#[serial]
+fn manifest_relative_sources()
+{
+    create_dir_all("manifest_test").unwrap();
+    write(
+        "manifest_test/manifest.toml",
+        "[[objects]]
+source = \"../../LICENSE.txt\"
+
+[[objects]]
+source = \"../tests/metadata.json\"
+name = \"data/metadata.json\"
+"
+    )
+    .unwrap();
+    pack(&["-T", "manifest_test/manifest.toml"])
+        .success()
+        .stdout("")
+        .stderr("");
+    list();
+    remove_dir_all("manifest_test").unwrap();
+    remove_file("manifest.bpx").unwrap();
+}
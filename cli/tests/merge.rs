@@ -0,0 +1,191 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fs::{create_dir_all, remove_dir_all, remove_file, write};
+
+use predicates::prelude::*;
+use serial_test::serial;
+
+mod common;
+
+use common::bpx;
+
+const EXPECTED_OUTPUT: &str = "Decoding object table:
+Name = 'a.txt', Size = 6 byte(s)
+Name = 'shared.txt', Size = 6 byte(s)
+Name = 'b.txt', Size = 6 byte(s)
+";
+
+fn create_packages()
+{
+    create_dir_all("merge_test").unwrap();
+    write("merge_test/a.txt", "first\n").unwrap();
+    write("merge_test/b.txt", "other\n").unwrap();
+    write("merge_test/shared1.txt", "from1\n").unwrap();
+    write("merge_test/shared2.txt", "from2\n").unwrap();
+    bpx(&[
+        "-f",
+        "merge1.bpx",
+        "pack",
+        "--metadata",
+        "tests/metadata.json",
+        "merge_test/a.txt=a.txt",
+        "merge_test/shared1.txt=shared.txt"
+    ])
+    .success();
+    bpx(&[
+        "-f",
+        "merge2.bpx",
+        "pack",
+        "merge_test/shared2.txt=shared.txt",
+        "merge_test/b.txt=b.txt"
+    ])
+    .success();
+    remove_dir_all("merge_test").unwrap();
+}
+
+fn cleanup()
+{
+    remove_file("merge1.bpx").unwrap();
+    remove_file("merge2.bpx").unwrap();
+    remove_file("merged.bpx").unwrap();
+}
+
+#[test]
+#[serial]
+fn merge()
+{
+    create_packages();
+    bpx(&["-f", "merged.bpx", "merge", "merge1.bpx", "merge2.bpx"])
+        .code(1)
+        .stderr(predicate::str::contains(
+            "object 'shared.txt' exists in both merge1.bpx and merge2.bpx"
+        ));
+    bpx(&[
+        "-f",
+        "merged.bpx",
+        "merge",
+        "--on-conflict",
+        "first",
+        "merge1.bpx",
+        "merge2.bpx"
+    ])
+    .success()
+    .stderr("");
+    bpx(&["-f", "merged.bpx", "list"])
+        .success()
+        .stdout(
+            predicate::str::starts_with(EXPECTED_OUTPUT)
+                .and(predicate::str::contains("Metadata:\n"))
+        )
+        .stderr("");
+    bpx(&["-f", "merged.bpx", "cat", "shared.txt"])
+        .success()
+        .stdout("from1\n");
+    bpx(&["-f", "merged.bpx", "info", "--metadata"])
+        .success()
+        .stdout(predicate::str::contains("1.2.0"));
+    bpx(&[
+        "-f",
+        "merged.bpx",
+        "merge",
+        "--on-conflict",
+        "last",
+        "merge1.bpx",
+        "merge2.bpx"
+    ])
+    .success()
+    .stderr("");
+    bpx(&["-f", "merged.bpx", "cat", "shared.txt"])
+        .success()
+        .stdout("from2\n");
+    bpx(&["-f", "merged.bpx", "cat", "a.txt"])
+        .success()
+        .stdout("first\n");
+    bpx(&["-f", "merged.bpx", "verify"]).success().stderr("");
+    cleanup();
+}
+
+#[test]
+#[serial]
+fn merge_keeps_stored_sections()
+{
+    create_packages();
+    bpx(&[
+        "-v",
+        "-f",
+        "merged.bpx",
+        "merge",
+        "--on-conflict",
+        "last",
+        "merge1.bpx",
+        "merge2.bpx"
+    ])
+    .success()
+    .stdout(
+        predicate::str::contains("Copying objects of merge1.bpx as stored...")
+            .and(predicate::str::contains(
+                "Recompressing shared.txt from merge2.bpx..."
+            ))
+            .and(predicate::str::contains(
+                "Recompressing b.txt from merge2.bpx..."
+            ))
+            .and(predicate::str::contains("a.txt from").not())
+    )
+    .stderr("");
+    bpx(&["-f", "merged.bpx", "cat", "shared.txt"])
+        .success()
+        .stdout("from2\n");
+    bpx(&[
+        "-v",
+        "-f",
+        "merged.bpx",
+        "merge",
+        "--compression",
+        "xz",
+        "--on-conflict",
+        "first",
+        "merge1.bpx",
+        "merge2.bpx"
+    ])
+    .success()
+    .stdout(
+        predicate::str::contains("Recompressing a.txt from merge1.bpx...")
+            .and(predicate::str::contains("as stored").not())
+    )
+    .stderr("");
+    bpx(&["-f", "merged.bpx", "list"])
+        .success()
+        .stdout(
+            predicate::str::starts_with(EXPECTED_OUTPUT)
+                .and(predicate::str::contains("Metadata:\n"))
+        )
+        .stderr("");
+    bpx(&["-f", "merged.bpx", "verify"]).success().stderr("");
+    cleanup();
+}
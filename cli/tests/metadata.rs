@@ -26,98 +26,79 @@
 // NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
 // SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use assert_cmd::Command;
-
-const EXPECTED_OUTPUT: &str = "====> BPX Main Header <====
-Type: P
-Version: 1
-File size: 1632
-Number of sections: 2
-====> End <====
-
-====> BPX TypeExt <====
-Architecture: Any
-Platform: Any
-Generator: BD
-====> End <====
-
-====> BPX Section Header Table <====
-Section #0:
-	Type: 255
-	Size (after compression): 15
-	Size: 15
-	Flags:  CheckWeak
-Section #1:
-	Type: 1
-	Size (after compression): 1529
-	Size: 1529
-	Flags:  CheckWeak
-====> End <====
+use std::fs::remove_file;
 
-";
-
-const EXPECTED_OUTPUT_HEX: &str = "====> BPX Main Header <====
-Type: P
-Version: 1
-File size: 1632
-Number of sections: 2
-====> End <====
-
-====> BPX TypeExt <====
-04 04 42 44 00 00 00 00 00 00 00 00 00 00 00 00 
-====> End <====
-
-====> BPX Section Header Table <====
-Section #0:
-	Type: 255
-	Size (after compression): 15
-	Size: 15
-	Flags:  CheckWeak
-Section #1:
-	Type: 1
-	Size (after compression): 1529
-	Size: 1529
-	Flags:  CheckWeak
-====> End <====
-
-";
+use assert_cmd::Command;
+use predicates::prelude::*;
+use serial_test::serial;
 
 #[test]
-fn dump_metadata_1()
+#[serial]
+fn pack_info()
 {
-    let assert = Command::cargo_bin("bpxdump")
+    let assert = Command::cargo_bin("bpx")
         .unwrap()
-        .args(&["-f", "tests/test.bpx", "-sm"])
+        .args(&[
+            "-f",
+            "metadata.bpx",
+            "pack",
+            "--platform",
+            "linux",
+            "--metadata",
+            "tests/metadata.json",
+            "../LICENSE.txt"
+        ])
         .assert();
-    assert.success().stdout(EXPECTED_OUTPUT).stderr("");
-}
-
-#[test]
-fn dump_metadata_2()
-{
-    let assert = Command::cargo_bin("bpxdump")
+    assert.success().stdout("").stderr("");
+    let assert = Command::cargo_bin("bpx")
         .unwrap()
-        .args(&["-f", "tests/test.bpx", "--sht", "--metadata"])
+        .args(&["-f", "metadata.bpx", "info", "--metadata"])
         .assert();
-    assert.success().stdout(EXPECTED_OUTPUT).stderr("");
-}
-
-#[test]
-fn dump_metadata_hex_1()
-{
-    let assert = Command::cargo_bin("bpxdump")
+    assert
+        .success()
+        .stdout(predicate::str::starts_with(
+            "Architecture: Any
+Platform: Linux
+Generator: BD
+Metadata:
+"
+        ))
+        .stdout(predicate::str::contains("\"version\": \"1.2.0\""))
+        .stdout(predicate::str::contains("\"build\": 4242"))
+        .stderr("");
+    let assert = Command::cargo_bin("bpx")
         .unwrap()
-        .args(&["-f", "tests/test.bpx", "-smx"])
+        .args(&["-f", "metadata.bpx", "list"])
         .assert();
-    assert.success().stdout(EXPECTED_OUTPUT_HEX).stderr("");
+    assert
+        .success()
+        .stdout(predicate::str::starts_with(
+            "Decoding object table:
+Name = 'LICENSE.txt', Size = 1518 byte(s)
+Metadata:
+"
+        ))
+        .stderr("");
+    remove_file("metadata.bpx").unwrap();
 }
 
 #[test]
-fn dump_metadata_hex_2()
+#[serial]
+fn invalid_metadata()
 {
-    let assert = Command::cargo_bin("bpxdump")
+    let assert = Command::cargo_bin("bpx")
         .unwrap()
-        .args(&["-f", "tests/test.bpx", "--sht", "--metadata", "--hex"])
+        .args(&[
+            "-f",
+            "metadata.bpx",
+            "pack",
+            "--metadata",
+            "../LICENSE.txt",
+            "../LICENSE.txt"
+        ])
         .assert();
-    assert.success().stdout(EXPECTED_OUTPUT_HEX).stderr("");
+    assert
+        .failure()
+        .stdout("")
+        .stderr(predicate::str::contains("could not parse metadata file"));
 }
@@ -0,0 +1,95 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fs::remove_file;
+
+use assert_cmd::Command;
+use serial_test::serial;
+
+fn pack_list(args: &[&str], expected: &str)
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "names.bpx", "pack"])
+        .args(args)
+        .assert();
+    assert.success().stdout("").stderr("");
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "names.bpx", "list"])
+        .assert();
+    assert.success().stdout(expected).stderr("");
+    remove_file("names.bpx").unwrap();
+}
+
+#[test]
+#[serial]
+fn prefix()
+{
+    pack_list(
+        &["--prefix", "data/docs", "../LICENSE.txt"],
+        "Decoding object table:
+Name = 'data/docs/LICENSE.txt', Size = 1518 byte(s)
+"
+    );
+}
+
+#[test]
+#[serial]
+fn mapping()
+{
+    pack_list(
+        &["../LICENSE.txt=legal/COPYING"],
+        "Decoding object table:
+Name = 'legal/COPYING', Size = 1518 byte(s)
+"
+    );
+}
+
+#[test]
+#[serial]
+fn base_dir()
+{
+    pack_list(
+        &["--base-dir", "..", "../cli/tests/test.bpx"],
+        "Decoding object table:
+Name = 'cli/tests/test.bpx', Size = 1632 byte(s)
+"
+    );
+}
+
+#[test]
+#[serial]
+fn invalid_name()
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "names.bpx", "pack", "../LICENSE.txt=../LICENSE.txt"])
+        .assert();
+    assert.failure().stdout("");
+}
@@ -26,73 +26,53 @@
 // NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
 // SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use assert_cmd::Command;
-
-const EXPECTED_OUTPUT_HEX: &str = "====> BPX Main Header <====
-Type: P
-Version: 1
-File size: 1632
-Number of sections: 2
-====> End <====
-
-4C 49 43 45 4E 53 45 5F 4C 46 2E 74 78 74 00 
-";
-
-const EXPECTED_OUTPUT_RAW: &str = "====> BPX Main Header <====
-Type: P
-Version: 1
-File size: 1632
-Number of sections: 2
-====> End <====
+use std::fs::{remove_dir_all, remove_file};
 
-";
+use assert_cmd::Command;
+use file_diff::diff;
+use serial_test::serial;
 
-const EXPECTED_ERROR_RAW: &str =
-    "Outputing binary data to standard output can mess-up your terminal, please use --force if you're sure to continue
+const EXPECTED_LIST: &str = "Decoding object table:
+Name = 'LICENSE.txt', Size = 1518 byte(s)
 ";
 
 #[test]
-fn dump_section_hex_1()
+#[serial]
+fn pack_list_unpack()
 {
-    let assert = Command::cargo_bin("bpxdump")
+    let assert = Command::cargo_bin("bpx")
         .unwrap()
-        .args(&["-f", "tests/test.bpx", "-xd", "0"])
+        .args(&["-f", "test.bpx", "pack", "../LICENSE.txt"])
         .assert();
-    assert.success().stdout(EXPECTED_OUTPUT_HEX).stderr("");
-}
-
-#[test]
-fn dump_section_hex_2()
-{
-    let assert = Command::cargo_bin("bpxdump")
+    assert.success().stdout("").stderr("");
+    let assert = Command::cargo_bin("bpx")
         .unwrap()
-        .args(&["-f", "tests/test.bpx", "--dump", "0", "--hex"])
+        .args(&["-f", "test.bpx", "list"])
         .assert();
-    assert.success().stdout(EXPECTED_OUTPUT_HEX).stderr("");
-}
-
-#[test]
-fn dump_section_raw_1()
-{
-    let assert = Command::cargo_bin("bpxdump")
+    assert.success().stdout(EXPECTED_LIST).stderr("");
+    let assert = Command::cargo_bin("bpx")
         .unwrap()
-        .args(&["-f", "tests/test.bpx", "-d", "0"])
+        .args(&["-f", "test.bpx", "unpack", "-C", "out"])
         .assert();
-    assert
-        .failure()
-        .stderr(EXPECTED_ERROR_RAW)
-        .stdout(EXPECTED_OUTPUT_RAW);
+    assert.success().stdout("").stderr("");
+    assert!(diff("out/LICENSE.txt", "../LICENSE.txt"));
+    remove_dir_all("out").unwrap();
+    remove_file("test.bpx").unwrap();
 }
 
 #[test]
-fn dump_section_raw_2()
+#[serial]
+fn pack_verify()
 {
-    let assert = Command::cargo_bin("bpxdump")
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "test.bpx", "pack", "../LICENSE.txt"])
+        .assert();
+    assert.success().stdout("").stderr("");
+    let assert = Command::cargo_bin("bpx")
         .unwrap()
-        .args(&["-f", "tests/test.bpx", "--dump", "0"])
+        .args(&["-f", "test.bpx", "verify"])
         .assert();
-    assert
-        .failure()
-        .stderr(EXPECTED_ERROR_RAW)
-        .stdout(EXPECTED_OUTPUT_RAW);
+    assert.success().stderr("");
+    remove_file("test.bpx").unwrap();
 }
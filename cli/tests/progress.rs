@@ -0,0 +1,67 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fs::{create_dir_all, remove_dir_all, remove_file, write};
+
+use predicates::prelude::*;
+use serial_test::serial;
+
+mod common;
+
+fn bpx(args: &[&str]) -> assert_cmd::assert::Assert
+{
+    common::bpx_file("progress.bpx", args)
+}
+
+#[test]
+#[serial]
+fn json_progress()
+{
+    create_dir_all("progress_test").unwrap();
+    write("progress_test/a.txt", "hello").unwrap();
+    write("progress_test/b.txt", "world!").unwrap();
+    bpx(&["pack", "--progress", "json", "progress_test"])
+        .success()
+        .stdout("")
+        .stderr(
+            predicate::str::contains("\"total_objects\":2")
+                .and(predicate::str::contains("\"total_bytes\":11"))
+                .and(predicate::str::contains("\"objects\":2"))
+        );
+    remove_dir_all("progress_test").unwrap();
+    bpx(&["unpack", "--progress", "json"])
+        .success()
+        .stdout("")
+        .stderr(
+            predicate::str::contains("\"bytes\":11").and(predicate::str::contains("\"objects\":2"))
+        );
+    //Progress is disabled by default when stderr is not a terminal
+    bpx(&["unpack"]).success().stdout("").stderr("");
+    remove_dir_all("progress_test").unwrap();
+    remove_file("progress.bpx").unwrap();
+}
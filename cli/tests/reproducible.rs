@@ -0,0 +1,103 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fs::{create_dir_all, remove_dir_all, remove_file, write};
+
+use assert_cmd::Command;
+use file_diff::diff;
+use serial_test::serial;
+
+fn create_input()
+{
+    create_dir_all("reproducible_test/b").unwrap();
+    write("reproducible_test/b/data.txt", "data").unwrap();
+    write("reproducible_test/a.txt", "a").unwrap();
+    write("reproducible_test/c.txt", "c").unwrap();
+}
+
+fn pack(file: &str)
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .env("SOURCE_DATE_EPOCH", "1000")
+        .args(&["-f", file, "pack", "--hashes", "reproducible_test"])
+        .assert();
+    assert.success().stdout("").stderr("");
+}
+
+#[test]
+#[serial]
+fn pack_twice()
+{
+    create_input();
+    pack("reproducible1.bpx");
+    //Recreate the inputs so that they get new modification times
+    remove_dir_all("reproducible_test").unwrap();
+    create_input();
+    pack("reproducible2.bpx");
+    assert!(diff("reproducible1.bpx", "reproducible2.bpx"));
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "reproducible1.bpx", "list"])
+        .assert();
+    assert
+        .success()
+        .stdout(
+            "Decoding object table:
+Name = 'reproducible_test/a.txt', Size = 1 byte(s)
+Name = 'reproducible_test/b/data.txt', Size = 4 byte(s)
+Name = 'reproducible_test/c.txt', Size = 1 byte(s)
+"
+        )
+        .stderr("");
+    remove_dir_all("reproducible_test").unwrap();
+    remove_file("reproducible1.bpx").unwrap();
+    remove_file("reproducible2.bpx").unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+#[serial]
+fn pack_with_umask()
+{
+    use std::{
+        fs::{set_permissions, Permissions},
+        os::unix::fs::PermissionsExt
+    };
+
+    //Only the group and other write permissions depend on the umask
+    for (i, mode) in [0o644, 0o664].iter().enumerate() {
+        create_input();
+        set_permissions("reproducible_test/a.txt", Permissions::from_mode(*mode)).unwrap();
+        pack(&format!("reproducible{}.bpx", i + 1));
+        remove_dir_all("reproducible_test").unwrap();
+    }
+    assert!(diff("reproducible1.bpx", "reproducible2.bpx"));
+    remove_file("reproducible1.bpx").unwrap();
+    remove_file("reproducible2.bpx").unwrap();
+}
@@ -0,0 +1,87 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fs::remove_file;
+
+use predicates::prelude::*;
+use serial_test::serial;
+
+mod common;
+
+use common::bpx_file;
+
+//Type of the BPXSD section holding the metadata of a BPXP
+const SECTION_TYPE_SD: u64 = 0xFE;
+
+fn metadata_section() -> String
+{
+    let assert = bpx_file("sd.bpx", &["--format", "json", "sht"]).success();
+    let json: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    let section = json["sections"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|v| v["type"] == SECTION_TYPE_SD)
+        .unwrap();
+    section["index"].to_string()
+}
+
+#[test]
+#[serial]
+fn sd()
+{
+    bpx_file(
+        "sd.bpx",
+        &[
+            "pack",
+            "--no-preserve",
+            "--metadata",
+            "tests/metadata.json",
+            "../LICENSE.txt"
+        ]
+    )
+    .success()
+    .stdout("")
+    .stderr("");
+    let section = metadata_section();
+    bpx_file("sd.bpx", &["sd", &section])
+        .success()
+        .stdout(
+            predicate::str::contains("version1.2.0\n")
+                .and(predicate::str::contains("build(Int64) 4242\n"))
+        )
+        .stderr("");
+    bpx_file("sd.bpx", &["--format", "json", "sd", &section])
+        .success()
+        .stdout("{\"build\":4242,\"version\":\"1.2.0\"}\n")
+        .stderr("");
+    bpx_file("sd.bpx", &["--format", "csv", "sd", &section])
+        .failure()
+        .stderr(predicate::str::contains("sd does not support --format csv"));
+    remove_file("sd.bpx").unwrap();
+}
@@ -40,14 +40,14 @@ Name = 'LICENSE.txt', Size = 1518 byte(s)
 #[serial]
 fn pack_unpack()
 {
-    let assert = Command::cargo_bin("bpxp")
+    let assert = Command::cargo_bin("bpx")
         .unwrap()
-        .args(&["-f", "test.bpx", "-p", "../LICENSE.txt"])
+        .args(&["-f", "test.bpx", "pack", "../LICENSE.txt"])
         .assert();
     assert.success().stdout("").stderr("");
-    let assert = Command::cargo_bin("bpxp")
+    let assert = Command::cargo_bin("bpx")
         .unwrap()
-        .args(&["-f", "test.bpx", "-u"])
+        .args(&["-f", "test.bpx", "unpack"])
         .assert();
     assert.success().stdout("").stderr("");
     assert!(diff("LICENSE.txt", "../LICENSE.txt"));
@@ -59,14 +59,14 @@ fn pack_unpack()
 #[serial]
 fn pack_list()
 {
-    let assert = Command::cargo_bin("bpxp")
+    let assert = Command::cargo_bin("bpx")
         .unwrap()
-        .args(&["-f", "test.bpx", "-p", "../LICENSE.txt"])
+        .args(&["-f", "test.bpx", "pack", "../LICENSE.txt"])
         .assert();
     assert.success().stdout("").stderr("");
-    let assert = Command::cargo_bin("bpxp")
+    let assert = Command::cargo_bin("bpx")
         .unwrap()
-        .args(&["-f", "test.bpx", "-l"])
+        .args(&["-f", "test.bpx", "list"])
         .assert();
     assert.success().stdout(EXPECTED_OUTPUT).stderr("");
     remove_file("test.bpx").unwrap();
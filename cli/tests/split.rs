@@ -0,0 +1,143 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    fs::{create_dir_all, metadata, remove_dir_all, remove_file, write},
+    path::Path
+};
+
+use predicates::prelude::*;
+use serial_test::serial;
+
+mod common;
+
+use common::bpx;
+
+const AUDIO_OUTPUT: &str = "Decoding object table:
+Name = 'audio/music.ogg', Size = 6 byte(s)
+Name = 'audio/sfx/hit.ogg', Size = 4 byte(s)
+";
+
+const REST_OUTPUT: &str = "Decoding object table:
+Name = 'readme.txt', Size = 7 byte(s)
+";
+
+#[test]
+#[serial]
+fn split_rules()
+{
+    create_dir_all("split_test/sfx").unwrap();
+    write("split_test/music.ogg", "music\n").unwrap();
+    write("split_test/sfx/hit.ogg", "hit\n").unwrap();
+    write("split_test/readme.txt", "readme\n").unwrap();
+    bpx(&[
+        "-f",
+        "split.bpx",
+        "pack",
+        "--arch",
+        "x86_64",
+        "--metadata",
+        "tests/metadata.json",
+        "split_test/music.ogg=audio/music.ogg",
+        "split_test/sfx/hit.ogg=audio/sfx/hit.ogg",
+        "split_test/readme.txt=readme.txt"
+    ])
+    .success();
+    remove_dir_all("split_test").unwrap();
+    bpx(&["-f", "split.bpx", "split", "-r", "audio/**=split_audio.bpx"])
+        .code(1)
+        .stderr(predicate::str::contains(
+            "object 'readme.txt' matches no rule"
+        ));
+    bpx(&[
+        "-f",
+        "split.bpx",
+        "split",
+        "-r",
+        "audio/**=split_audio.bpx",
+        "--rest",
+        "split_rest.bpx"
+    ])
+    .success()
+    .stdout("")
+    .stderr("");
+    bpx(&["-f", "split_audio.bpx", "list"])
+        .success()
+        .stdout(
+            predicate::str::starts_with(AUDIO_OUTPUT).and(predicate::str::contains("Metadata:\n"))
+        )
+        .stderr("");
+    bpx(&["-f", "split_rest.bpx", "list"])
+        .success()
+        .stdout(
+            predicate::str::starts_with(REST_OUTPUT).and(predicate::str::contains("Metadata:\n"))
+        )
+        .stderr("");
+    bpx(&["-f", "split_audio.bpx", "cat", "audio/sfx/hit.ogg"])
+        .success()
+        .stdout("hit\n");
+    for output in &["split_audio.bpx", "split_rest.bpx"] {
+        bpx(&["-f", output, "info", "--metadata"]).success().stdout(
+            predicate::str::contains("Architecture: x86_64").and(predicate::str::contains("1.2.0"))
+        );
+    }
+    remove_file("split.bpx").unwrap();
+    remove_file("split_audio.bpx").unwrap();
+    remove_file("split_rest.bpx").unwrap();
+}
+
+#[test]
+#[serial]
+fn split_size()
+{
+    create_dir_all("split_test").unwrap();
+    for name in &["a.bin", "b.bin", "c.bin"] {
+        write(format!("split_test/{}", name), [0x42; 1000]).unwrap();
+    }
+    bpx(&["-f", "split_size.bpx", "pack", "split_test"]).success();
+    remove_dir_all("split_test").unwrap();
+    bpx(&["-f", "split_size.bpx", "split", "--max-size", "100"])
+        .code(1)
+        .stderr(predicate::str::contains("does not fit in 100 byte(s)"));
+    assert!(!Path::new("split_size.1.bpx").exists());
+    bpx(&["-f", "split_size.bpx", "split", "--max-size", "2600"])
+        .success()
+        .stderr("");
+    for output in &["split_size.1.bpx", "split_size.2.bpx"] {
+        assert!(metadata(output).unwrap().len() <= 2600);
+    }
+    bpx(&["-f", "split_size.1.bpx", "list"])
+        .success()
+        .stdout(predicate::str::contains("a.bin").and(predicate::str::contains("b.bin")));
+    bpx(&["-f", "split_size.2.bpx", "list"])
+        .success()
+        .stdout(predicate::str::contains("c.bin").and(predicate::str::contains("a.bin").not()));
+    remove_file("split_size.bpx").unwrap();
+    remove_file("split_size.1.bpx").unwrap();
+    remove_file("split_size.2.bpx").unwrap();
+}
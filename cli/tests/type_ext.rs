@@ -0,0 +1,121 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fs::remove_file;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use serial_test::serial;
+
+const EXPECTED_TYPE_EXT: &str = "====> BPX TypeExt <====
+Architecture: aarch64
+Platform: Android
+Generator: GM
+====> End <====
+";
+
+#[test]
+#[serial]
+fn pack_type_ext()
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&[
+            "-f",
+            "type_ext.bpx",
+            "pack",
+            "--arch",
+            "aarch64",
+            "--platform",
+            "android",
+            "--generator",
+            "GM",
+            "../LICENSE.txt"
+        ])
+        .assert();
+    assert.success().stdout("").stderr("");
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "type_ext.bpx", "info"])
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains(EXPECTED_TYPE_EXT))
+        .stderr("");
+    remove_file("type_ext.bpx").unwrap();
+}
+
+#[test]
+#[serial]
+fn invalid_generator()
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&[
+            "-f",
+            "type_ext.bpx",
+            "pack",
+            "--generator",
+            "ABC",
+            "../LICENSE.txt"
+        ])
+        .assert();
+    assert
+        .failure()
+        .stdout("")
+        .stderr(predicate::str::contains("Input error:"));
+}
+
+#[test]
+#[serial]
+fn dump_diff_type_ext()
+{
+    for (file, arch) in [("type_ext1.bpx", "x86_64"), ("type_ext2.bpx", "aarch64")] {
+        let assert = Command::cargo_bin("bpx")
+            .unwrap()
+            .args(&["-f", file, "pack", "--arch", arch, "../LICENSE.txt"])
+            .assert();
+        assert.success().stdout("").stderr("");
+    }
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&[
+            "-f",
+            "type_ext1.bpx",
+            "diff",
+            "--container",
+            "type_ext2.bpx"
+        ])
+        .assert();
+    assert
+        .code(1)
+        .stdout(predicate::str::contains("TypeExt: differs at byte offset"))
+        .stderr("");
+    remove_file("type_ext1.bpx").unwrap();
+    remove_file("type_ext2.bpx").unwrap();
+}
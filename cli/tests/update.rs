@@ -0,0 +1,155 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fs::remove_file;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use serial_test::serial;
+
+fn bpx(args: &[&str])
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "update.bpx"])
+        .args(args)
+        .assert();
+    assert.success().stdout("").stderr("");
+}
+
+fn list(expected: &str)
+{
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "update.bpx", "list"])
+        .assert();
+    assert.success().stdout(expected).stderr("");
+}
+
+#[test]
+#[serial]
+fn add_replace_delete()
+{
+    bpx(&["pack", "../LICENSE.txt", "tests/metadata.json"]);
+    bpx(&["update", "--delete", "metadata.json"]);
+    list(
+        "Decoding object table:
+Name = 'LICENSE.txt', Size = 1518 byte(s)
+"
+    );
+    bpx(&["update", "--add", "tests/test.bpx"]);
+    list(
+        "Decoding object table:
+Name = 'LICENSE.txt', Size = 1518 byte(s)
+Name = 'test.bpx', Size = 1632 byte(s)
+"
+    );
+    bpx(&["update", "--replace", "tests/test.bpx=LICENSE.txt"]);
+    list(
+        "Decoding object table:
+Name = 'test.bpx', Size = 1632 byte(s)
+Name = 'LICENSE.txt', Size = 1632 byte(s)
+"
+    );
+    remove_file("update.bpx").unwrap();
+}
+
+#[test]
+#[serial]
+fn errors()
+{
+    bpx(&["pack", "../LICENSE.txt"]);
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "update.bpx", "update", "--delete", "missing.txt"])
+        .assert();
+    assert
+        .failure()
+        .stderr(predicate::str::contains("cannot delete 'missing.txt'"));
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .args(&["-f", "update.bpx", "update", "--add", "../LICENSE.txt"])
+        .assert();
+    assert
+        .failure()
+        .stderr(predicate::str::contains("already exists in package"));
+    list(
+        "Decoding object table:
+Name = 'LICENSE.txt', Size = 1518 byte(s)
+"
+    );
+    remove_file("update.bpx").unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+#[serial]
+fn attributes()
+{
+    use std::{
+        fs::{create_dir_all, metadata, remove_dir_all, set_permissions, write, Permissions},
+        os::unix::fs::PermissionsExt,
+        time::{Duration, UNIX_EPOCH}
+    };
+
+    create_dir_all("update_test").unwrap();
+    write("update_test/tool.sh", "#!/bin/sh\n").unwrap();
+    set_permissions("update_test/tool.sh", Permissions::from_mode(0o750)).unwrap();
+    bpx(&["pack", "../LICENSE.txt"]);
+    let assert = Command::cargo_bin("bpx")
+        .unwrap()
+        .env("SOURCE_DATE_EPOCH", "1000")
+        .args(&[
+            "-f",
+            "update.bpx",
+            "update",
+            "--add",
+            "update_test/tool.sh=update_test/added.sh"
+        ])
+        .assert();
+    assert.success().stdout("").stderr("");
+    bpx(&[
+        "update",
+        "--no-preserve",
+        "--add",
+        "update_test/tool.sh=update_test/plain.sh"
+    ]);
+    remove_dir_all("update_test").unwrap();
+    bpx(&["unpack"]);
+    let added = metadata("update_test/added.sh").unwrap();
+    assert_eq!(added.permissions().mode() & 0o777, 0o750);
+    assert_eq!(
+        added.modified().unwrap(),
+        UNIX_EPOCH + Duration::from_secs(1000)
+    );
+    let plain = metadata("update_test/plain.sh").unwrap();
+    assert_eq!(plain.permissions().mode() & 0o111, 0);
+    remove_dir_all("update_test").unwrap();
+    remove_file("LICENSE.txt").unwrap();
+    remove_file("update.bpx").unwrap();
+}
@@ -0,0 +1,198 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{BufReader, Read, Seek},
+    path::Path
+};
+
+use bpx::core::Container;
+
+use crate::dump::error::Result;
+
+/// A difference found by [run].
+pub enum Difference
+{
+    /// Part of the container ("Main header" or "Section #N"), field, and the values of both files.
+    Field(String, &'static str, String, String),
+    /// Byte offset of the first difference in the TypeExt block.
+    TypeExt(u64),
+    /// Index of a section missing in the other file.
+    MissingSection(u32),
+    /// Index of a section only present in the other file.
+    ExtraSection(u32),
+    /// Index of a section and byte offset of the first difference in its data.
+    SectionData(u32, u64)
+}
+
+fn compare<V: PartialEq + Display>(
+    diffs: &mut Vec<Difference>,
+    what: &str,
+    field: &'static str,
+    a: V,
+    b: V
+)
+{
+    if a != b {
+        diffs.push(Difference::Field(
+            what.into(),
+            field,
+            a.to_string(),
+            b.to_string()
+        ));
+    }
+}
+
+//Reads until the buffer is full or the end of the stream is reached
+fn fill<R: Read>(reader: &mut R, buf: &mut [u8]) -> std::io::Result<usize>
+{
+    let mut len = 0;
+    while len < buf.len() {
+        match reader.read(&mut buf[len..])? {
+            0 => break,
+            n => len += n
+        }
+    }
+    Ok(len)
+}
+
+//Returns the offset of the first byte which differs, the end of the shortest stream counts as a
+//difference
+fn first_difference<A: Read, B: Read>(mut a: A, mut b: B) -> std::io::Result<Option<u64>>
+{
+    let mut buf_a: [u8; 8192] = [0; 8192];
+    let mut buf_b: [u8; 8192] = [0; 8192];
+    let mut offset = 0;
+    loop {
+        let len_a = fill(&mut a, &mut buf_a)?;
+        let len_b = fill(&mut b, &mut buf_b)?;
+        let len = len_a.min(len_b);
+        let pos = buf_a[..len]
+            .iter()
+            .zip(&buf_b[..len])
+            .position(|(x, y)| x != y);
+        if let Some(pos) = pos {
+            return Ok(Some(offset + pos as u64));
+        }
+        if len_a != len_b {
+            return Ok(Some(offset + len as u64));
+        }
+        if len == 0 {
+            return Ok(None);
+        }
+        offset += len as u64;
+    }
+}
+
+fn diff_main_header<T>(
+    a: &Container<T>,
+    b: &Container<T>,
+    diffs: &mut Vec<Difference>
+) -> Result<()>
+{
+    let (a, b) = (a.get_main_header(), b.get_main_header());
+    compare(
+        diffs,
+        "Main header",
+        "Type",
+        a.btype as char,
+        b.btype as char
+    );
+    compare(diffs, "Main header", "Version", a.version, b.version);
+    compare(diffs, "Main header", "File size", a.file_size, b.file_size);
+    compare(
+        diffs,
+        "Main header",
+        "Number of sections",
+        a.section_num,
+        b.section_num
+    );
+    if let Some(offset) = first_difference(&a.type_ext[..], &b.type_ext[..])? {
+        diffs.push(Difference::TypeExt(offset));
+    }
+    Ok(())
+}
+
+//Sections are paired by their position in the SHT
+fn diff_sht<T>(a: &Container<T>, b: &Container<T>, diffs: &mut Vec<Difference>) -> Vec<(u32, u32)>
+{
+    let mut pairs = Vec::new();
+    for (a, b) in a.iter().zip(b.iter()) {
+        let what = format!("Section #{}", a.index());
+        compare(diffs, &what, "Type", a.btype, b.btype);
+        compare(diffs, &what, "Flags", a.flags, b.flags);
+        compare(diffs, &what, "Size (after compression)", a.csize, b.csize);
+        compare(diffs, &what, "Size", a.size, b.size);
+        pairs.push((a.index(), b.index()));
+    }
+    for v in a.iter().skip(pairs.len()) {
+        diffs.push(Difference::MissingSection(v.index()));
+    }
+    for v in b.iter().skip(pairs.len()) {
+        diffs.push(Difference::ExtraSection(v.index()));
+    }
+    pairs
+}
+
+fn diff_section<T: Read + Seek>(
+    a: &mut Container<T>,
+    b: &mut Container<T>,
+    index_a: u32,
+    index_b: u32
+) -> Result<Option<Difference>>
+{
+    let (handle_a, handle_b) = match (
+        a.find_section_by_index(index_a),
+        b.find_section_by_index(index_b)
+    ) {
+        (Some(handle_a), Some(handle_b)) => (handle_a, handle_b),
+        _ => return Ok(None)
+    };
+    let mut section_a = a.get_mut(handle_a);
+    let mut section_b = b.get_mut(handle_b);
+    let offset = first_difference(section_a.load()?, section_b.load()?)?;
+    Ok(offset.map(|v| Difference::SectionData(index_a, v)))
+}
+
+/// Compares the headers, TypeExt, section header tables and section data of two BPX containers;
+/// the files are identical when no difference is returned.
+pub fn run(file: &Path, other: &Path) -> Result<Vec<Difference>>
+{
+    let mut a = Container::open(BufReader::new(File::open(file)?))?;
+    let mut b = Container::open(BufReader::new(File::open(other)?))?;
+    let mut diffs = Vec::new();
+
+    diff_main_header(&a, &b, &mut diffs)?;
+    let pairs = diff_sht(&a, &b, &mut diffs);
+    for (index_a, index_b) in pairs {
+        diffs.extend(diff_section(&mut a, &mut b, index_a, index_b)?);
+    }
+    Ok(diffs)
+}
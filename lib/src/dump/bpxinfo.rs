@@ -0,0 +1,341 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    fs::File,
+    io::{BufReader, Read, Seek, Write},
+    path::{Path, PathBuf},
+    string::String
+};
+
+use bpx::core::{
+    header::{FLAG_CHECK_CRC32, FLAG_CHECK_WEAK, FLAG_COMPRESS_XZ, FLAG_COMPRESS_ZLIB},
+    Container,
+    SectionMut
+};
+
+use super::type_ext_maps::{get_type_ext_json_map, get_type_ext_map};
+use crate::dump::error::{Error, Result};
+
+fn print_main_header<T, TWrite: Write>(bpx: &Container<T>, out: &mut TWrite) -> Result<()>
+{
+    writeln!(out, "====> BPX Main Header <====")?;
+    writeln!(out, "Type: {}", bpx.get_main_header().btype as char)?;
+    writeln!(out, "Version: {}", bpx.get_main_header().version)?;
+    writeln!(out, "File size: {}", bpx.get_main_header().file_size)?;
+    writeln!(
+        out,
+        "Number of sections: {}",
+        bpx.get_main_header().section_num
+    )?;
+    writeln!(out, "====> End <====")?;
+    writeln!(out)?;
+    Ok(())
+}
+
+fn flag_names(flags: u8) -> Vec<&'static str>
+{
+    let mut names = Vec::new();
+    if flags & FLAG_COMPRESS_ZLIB == FLAG_COMPRESS_ZLIB {
+        names.push("CompressZlib");
+    }
+    if flags & FLAG_COMPRESS_XZ == FLAG_COMPRESS_XZ {
+        names.push("CompressXZ");
+    }
+    if flags & FLAG_CHECK_CRC32 == FLAG_CHECK_CRC32 {
+        names.push("CheckCrc32");
+    }
+    if flags & FLAG_CHECK_WEAK == FLAG_CHECK_WEAK {
+        names.push("CheckWeak");
+    }
+    if flags & FLAG_CHECK_WEAK != FLAG_CHECK_WEAK && flags & FLAG_CHECK_CRC32 != FLAG_CHECK_CRC32 {
+        names.push("CheckNone");
+    }
+    names
+}
+
+fn print_sht<T, TWrite: Write>(bpx: &Container<T>, out: &mut TWrite) -> Result<()>
+{
+    writeln!(out, "====> BPX Section Header Table <====")?;
+    for v in bpx.iter() {
+        writeln!(out, "Section #{}:", v.index())?;
+        writeln!(out, "\tType: {}", v.btype)?;
+        writeln!(out, "\tSize (after compression): {}", v.csize)?;
+        writeln!(out, "\tSize: {}", v.size)?;
+        writeln!(out, "\tFlags:  {}", flag_names(v.flags).join(" | "))?;
+    }
+    writeln!(out, "====> End <====")?;
+    writeln!(out)?;
+    Ok(())
+}
+
+fn hex_print<TWrite: Write>(block: &[u8], output: &mut TWrite) -> Result<()>
+{
+    for (i, byte) in block.iter().enumerate() {
+        if i != 0 && i % 16 == 0 {
+            writeln!(output)?;
+        }
+        write!(output, "{:02X} ", byte)?;
+    }
+    Ok(())
+}
+
+fn print_metadata<T, TWrite: Write>(bpx: &Container<T>, hex: bool, out: &mut TWrite) -> Result<()>
+{
+    writeln!(out, "====> BPX TypeExt <====")?;
+    let type_ext = &bpx.get_main_header().type_ext;
+    match get_type_ext_map(bpx.get_main_header().btype) {
+        Some(func) if !hex => func(type_ext, out)?,
+        _ => {
+            hex_print(type_ext, out)?;
+            writeln!(out)?;
+        }
+    }
+    writeln!(out, "====> End <====")?;
+    writeln!(out)?;
+    Ok(())
+}
+
+fn print_section_hex<T: Read + Seek, TWrite: Write>(
+    mut section: SectionMut<T>,
+    out: &mut TWrite
+) -> Result<()>
+{
+    let rin = section.load()?;
+    let mut buf: [u8; 8192] = [0; 8192];
+    let mut res = rin.read(&mut buf)?;
+    while res > 0 {
+        hex_print(&buf[0..res], out)?;
+        res = rin.read(&mut buf)?;
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+fn print_section_sd<T: Read + Seek, TWrite: Write>(
+    mut section: SectionMut<T>,
+    out: &mut TWrite
+) -> Result<()>
+{
+    let rin = section.load()?;
+    let object = bpx::sd::Object::read(rin)?;
+    super::printsd::print_object(1, &object, out)?;
+    Ok(())
+}
+
+fn print_section_raw<T: Read + Seek, TWrite: Write>(
+    mut section: SectionMut<T>,
+    out: &mut TWrite
+) -> Result<()>
+{
+    let rin = section.load()?;
+    let mut buf: [u8; 8192] = [0; 8192];
+    let mut res = rin.read(&mut buf)?;
+    while res > 0 {
+        out.write_all(&buf[0..res])?;
+        res = rin.read(&mut buf)?;
+    }
+    Ok(())
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum PrintFormat
+{
+    Hex,
+    Sd,
+    Raw
+}
+
+struct SectionPrint<TWrite: Write>
+{
+    section_id: u32,
+    output: TWrite,
+    format: PrintFormat
+}
+
+/// Selects what [run] prints about a BPX container.
+#[derive(Clone, Default)]
+pub struct PrintOptions
+{
+    /// Print the main header.
+    pub header: bool,
+    /// Print the TypeExt block.
+    pub metadata: bool,
+    /// Print the section header table.
+    pub sht: bool,
+    /// Print the TypeExt block and the dumped section in hex.
+    pub hex: bool,
+    /// Index of the section to dump.
+    pub section: Option<u32>,
+    /// Decode the dumped section as a BPX Structured Data Object (BPXSD).
+    pub bpxsd: bool,
+    /// Write the dumped section to this file instead of the writer given to [run].
+    pub output: Option<PathBuf>,
+    /// Allow dumping raw section data.
+    pub force: bool
+}
+
+fn open_section_print<T: Read + Seek, TWrite: Write>(
+    bpx: &mut Container<T>,
+    mut opts: SectionPrint<TWrite>
+) -> Result<()>
+{
+    let section_id = opts.section_id;
+    let section = match bpx.find_section_by_index(section_id) {
+        Some(section) => section,
+        None => return Err(Error::SectionNotFound(section_id))
+    };
+    let section = bpx.get_mut(section);
+    match opts.format {
+        PrintFormat::Hex => print_section_hex(section, &mut opts.output),
+        PrintFormat::Sd => print_section_sd(section, &mut opts.output),
+        PrintFormat::Raw => print_section_raw(section, &mut opts.output)
+    }
+}
+
+/// Writes the parts of a BPX container selected in `options` to `out`.
+pub fn run<TWrite: Write>(file: &Path, options: &PrintOptions, out: &mut TWrite) -> Result<()>
+{
+    let mut bpx = Container::open(BufReader::new(File::open(file)?))?;
+
+    if options.header {
+        print_main_header(&bpx, out)?;
+    }
+    if options.metadata {
+        print_metadata(&bpx, options.hex, out)?;
+    }
+    if options.sht {
+        print_sht(&bpx, out)?;
+    }
+    if let Some(section_id) = options.section {
+        let format = {
+            if options.bpxsd {
+                PrintFormat::Sd
+            } else if options.hex {
+                PrintFormat::Hex
+            } else {
+                PrintFormat::Raw
+            }
+        };
+        if format == PrintFormat::Raw && !options.force {
+            return Err(Error::BinaryOutput);
+        }
+        match &options.output {
+            None => {
+                open_section_print(
+                    &mut bpx,
+                    SectionPrint {
+                        format,
+                        section_id,
+                        output: out
+                    }
+                )?;
+            },
+            Some(s) => {
+                open_section_print(
+                    &mut bpx,
+                    SectionPrint {
+                        format,
+                        section_id,
+                        output: File::create(s)?
+                    }
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the main header, TypeExt and section header table of a BPX container as selected in
+/// `options`; section dumps are not included.
+pub fn to_json(file: &Path, options: &PrintOptions) -> Result<serde_json::Value>
+{
+    let bpx = Container::open(BufReader::new(File::open(file)?))?;
+    let header = bpx.get_main_header();
+    let mut json = serde_json::Map::new();
+
+    if options.header {
+        let header = serde_json::json!({
+            "type": (header.btype as char).to_string(),
+            "version": header.version,
+            "file_size": header.file_size,
+            "section_num": header.section_num
+        });
+        json.insert("header".into(), header);
+    }
+    if options.metadata {
+        let type_ext = match get_type_ext_json_map(header.btype) {
+            Some(func) => func(&header.type_ext),
+            None => {
+                let bytes: Vec<String> = header
+                    .type_ext
+                    .iter()
+                    .map(|v| format!("{:02X}", v))
+                    .collect();
+                bytes.join(" ").into()
+            }
+        };
+        json.insert("type_ext".into(), type_ext);
+    }
+    if options.sht {
+        let sections: Vec<serde_json::Value> = bpx
+            .iter()
+            .map(|v| {
+                serde_json::json!({
+                    "index": v.index(),
+                    "type": v.btype,
+                    "csize": v.csize,
+                    "size": v.size,
+                    "flags": flag_names(v.flags)
+                })
+            })
+            .collect();
+        json.insert("sections".into(), sections.into());
+    }
+    Ok(serde_json::Value::Object(json))
+}
+
+/// Returns the type byte of the main header of a BPX container.
+pub fn container_type(file: &Path) -> Result<u8>
+{
+    let bpx = Container::open(BufReader::new(File::open(file)?))?;
+    Ok(bpx.get_main_header().btype)
+}
+
+/// Decodes a section of a BPX container as a BPX Structured Data Object (BPXSD).
+pub fn read_sd(file: &Path, section_id: u32) -> Result<bpx::sd::Object>
+{
+    let mut bpx = Container::open(BufReader::new(File::open(file)?))?;
+    let section = match bpx.find_section_by_index(section_id) {
+        Some(section) => section,
+        None => return Err(Error::SectionNotFound(section_id))
+    };
+    let mut section = bpx.get_mut(section);
+    let rin = section.load()?;
+    Ok(bpx::sd::Object::read(rin)?)
+}
@@ -30,6 +30,7 @@ use std::fmt::{Display, Formatter};
 
 use bpx::macros::impl_err_conversion;
 
+/// Error returned by the functions of [dump](crate::dump).
 pub enum Error
 {
     Bpx(bpx::core::error::ReadError),
@@ -63,4 +64,5 @@ impl Display for Error
     }
 }
 
+/// Result of the functions of [dump](crate::dump).
 pub type Result<T> = std::result::Result<T, Error>;
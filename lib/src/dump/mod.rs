@@ -26,37 +26,10 @@
 // NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
 // SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use assert_cmd::Command;
-use predicates::prelude::*;
+//! Inspection of raw BPX containers of any type.
 
-const EXPECTED_OUTPUT: &str = "====> BPX Main Header <====
-Type: P
-Version: 1
-File size: 1632
-Number of sections: 2
-====> End <====
-
-";
-
-#[test]
-fn dump_header()
-{
-    let assert = Command::cargo_bin("bpxdump")
-        .unwrap()
-        .args(&["-f", "tests/test.bpx"])
-        .assert();
-    assert.success().stdout(EXPECTED_OUTPUT).stderr("");
-}
-
-#[test]
-fn error()
-{
-    let assert = Command::cargo_bin("bpxdump")
-        .unwrap()
-        .args(&["-f", "doesnotexist.bpx"])
-        .assert();
-    assert
-        .failure()
-        .stdout("")
-        .stderr(predicate::str::contains("IO error:"));
-}
+pub mod bpxdiff;
+pub mod bpxinfo;
+pub mod error;
+pub mod printsd;
+mod type_ext_maps;
@@ -30,7 +30,7 @@ use std::{io::Write, string::String};
 
 use bpx::sd::{Array, DebugSymbols, Object, Value};
 
-use crate::error::Result;
+use crate::dump::error::Result;
 
 fn gen_layer_prefix(layer: usize) -> String
 {
@@ -80,6 +80,8 @@ fn print_array<TWrite: Write>(layer: usize, array: &Array, out: &mut TWrite) ->
     Ok(())
 }
 
+/// Writes a BPXSD object to `out` using the names of its debug layer when present, `layer` is the
+/// indentation level of its keys.
 pub fn print_object<TWrite: Write>(layer: usize, object: &Object, out: &mut TWrite) -> Result<()>
 {
     let prefix = gen_layer_prefix(layer);
@@ -102,6 +104,6 @@ pub fn print_object<TWrite: Write>(layer: usize, object: &Object, out: &mut TWri
         };
         print_value(layer, &object[*key], out)?;
     }
-    println!("{}}}", gen_layer_prefix(layer - 1));
+    writeln!(out, "{}}}", gen_layer_prefix(layer - 1))?;
     Ok(())
 }
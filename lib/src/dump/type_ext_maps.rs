@@ -26,31 +26,63 @@
 // NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
 // SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-fn bpxp_type_ext_map(block: &[u8; 16])
+use std::io::Write;
+
+fn architecture_name(code: u8) -> &'static str
 {
-    match block[0] {
-        0x0 => println!("Architecture: x86_64"),
-        0x1 => println!("Architecture: aarch64"),
-        0x2 => println!("Architecture: x86"),
-        0x3 => println!("Architecture: armv7hl"),
-        0x4 => println!("Architecture: Any"),
-        _ => println!("Architecture: Unknown")
+    match code {
+        0x0 => "x86_64",
+        0x1 => "aarch64",
+        0x2 => "x86",
+        0x3 => "armv7hl",
+        0x4 => "Any",
+        _ => "Unknown"
     }
-    match block[1] {
-        0x0 => println!("Platform: Linux"),
-        0x1 => println!("Platform: Mac"),
-        0x2 => println!("Platform: Windows"),
-        0x3 => println!("Platform: Android"),
-        0x4 => println!("Platform: Any"),
-        _ => println!("Platform: Unknown")
+}
+
+fn platform_name(code: u8) -> &'static str
+{
+    match code {
+        0x0 => "Linux",
+        0x1 => "Mac",
+        0x2 => "Windows",
+        0x3 => "Android",
+        0x4 => "Any",
+        _ => "Unknown"
     }
-    println!("Generator: {}{}", block[2] as char, block[3] as char);
 }
 
-pub fn get_type_ext_map(btype: u8) -> Option<fn(block: &[u8; 16])>
+fn bpxp_type_ext_map(block: &[u8; 16], out: &mut dyn Write) -> std::io::Result<()>
+{
+    writeln!(out, "Architecture: {}", architecture_name(block[0]))?;
+    writeln!(out, "Platform: {}", platform_name(block[1]))?;
+    writeln!(out, "Generator: {}{}", block[2] as char, block[3] as char)
+}
+
+fn bpxp_type_ext_json(block: &[u8; 16]) -> serde_json::Value
+{
+    serde_json::json!({
+        "architecture": architecture_name(block[0]),
+        "platform": platform_name(block[1]),
+        "generator": format!("{}{}", block[2] as char, block[3] as char)
+    })
+}
+
+type TypeExtMap = fn(block: &[u8; 16], out: &mut dyn Write) -> std::io::Result<()>;
+
+pub(super) fn get_type_ext_map(btype: u8) -> Option<TypeExtMap>
 {
     match btype {
         0x50 => Some(bpxp_type_ext_map),
         _ => None
     }
 }
+
+pub(super) fn get_type_ext_json_map(btype: u8)
+    -> Option<fn(block: &[u8; 16]) -> serde_json::Value>
+{
+    match btype {
+        0x50 => Some(bpxp_type_ext_json),
+        _ => None
+    }
+}
@@ -0,0 +1,513 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom},
+    path::Path
+};
+
+use bpx::{package::Package, sd::Object};
+use flate2::read::GzDecoder;
+use tar::{EntryType, Header};
+use zip::{write::FileOptions, DateTime, ZipArchive, ZipWriter};
+
+use crate::package::{
+    attributes::{insert_attributes, AttributeTable, Attributes},
+    error::{PackError, UnpackError},
+    hash::{insert_hashes, HashReader, HashWriter, Hashes},
+    input::normalize_name,
+    metadata,
+    output,
+    pack::{create_builder, PackOptions}
+};
+
+#[derive(Copy, Clone)]
+enum Format
+{
+    Tar,
+    TarGz,
+    Zip
+}
+
+fn detect_format(path: &Path) -> Option<Format>
+{
+    let name = path.file_name()?.to_str()?.to_lowercase();
+    if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Some(Format::TarGz)
+    } else if name.ends_with(".tar") {
+        Some(Format::Tar)
+    } else if name.ends_with(".zip") {
+        Some(Format::Zip)
+    } else {
+        None
+    }
+}
+
+//Zip archives store times in an unspecified local time zone, they are interpreted as UTC
+fn zip_time(time: DateTime) -> i64
+{
+    let (year, month, day) = (time.year() as i64, time.month() as i64, time.day() as i64);
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+    days * 86400 + time.hour() as i64 * 3600 + time.minute() as i64 * 60 + time.second() as i64
+}
+
+//Returns None for times which can't be represented in a zip archive (before 1980 or after 2107)
+fn zip_date(time: i64) -> Option<DateTime>
+{
+    let (days, secs) = (time.div_euclid(86400), time.rem_euclid(86400));
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    DateTime::from_date_and_time(
+        u16::try_from(year).ok()?,
+        month as u8,
+        day as u8,
+        (secs / 3600) as u8,
+        (secs % 3600 / 60) as u8,
+        (secs % 60) as u8
+    )
+    .ok()
+}
+
+struct Entry
+{
+    name: String,
+    attributes: Attributes,
+    /// Name of the entry holding the data of a hard link.
+    hard_link: Option<String>
+}
+
+fn visit_tar<R, F>(reader: R, mut f: F) -> Result<(), PackError>
+where
+    R: Read,
+    F: FnMut(Entry, &mut dyn Read) -> Result<(), PackError>
+{
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let mut hard_link = None;
+        let link = match entry.header().entry_type() {
+            EntryType::Regular | EntryType::Continuous => None,
+            EntryType::Symlink => entry
+                .link_name()?
+                .map(|v| v.to_string_lossy().replace('\\', "/")),
+            EntryType::Link => {
+                if let Some(target) = entry.link_name()? {
+                    hard_link = Some(normalize_name(&target.to_string_lossy())?);
+                }
+                None
+            },
+            EntryType::Directory | EntryType::XGlobalHeader => continue,
+            _ => {
+                return Err(PackError::Input(format!(
+                    "cannot import '{}': unsupported tar entry type",
+                    entry.path()?.display()
+                )))
+            },
+        };
+        let attributes = Attributes {
+            mode: entry.header().mode().ok().map(|v| v & 0o7777),
+            mtime: entry.header().mtime().ok().map(|v| v as i64),
+            link
+        };
+        let name = normalize_name(&entry.path()?.to_string_lossy())?;
+        let entry_data = Entry {
+            name,
+            attributes,
+            hard_link
+        };
+        f(entry_data, &mut entry)?;
+    }
+    Ok(())
+}
+
+fn visit_zip<F>(file: File, mut f: F) -> Result<(), PackError>
+where
+    F: FnMut(Entry, &mut dyn Read) -> Result<(), PackError>
+{
+    let mut archive = ZipArchive::new(BufReader::new(file))?;
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        if file.is_dir() {
+            continue;
+        }
+        let mode = file.unix_mode();
+        let mut attributes = Attributes {
+            mode: mode.map(|v| v & 0o7777),
+            mtime: Some(zip_time(file.last_modified())),
+            link: None
+        };
+        let name = normalize_name(file.name())?;
+        //Symbolic links are stored as files containing the target of the link
+        if mode.map(|v| v & 0o170000 == 0o120000).unwrap_or_default() {
+            let mut target = String::new();
+            file.read_to_string(&mut target)?;
+            attributes.link = Some(target);
+            f(
+                Entry {
+                    name,
+                    attributes,
+                    hard_link: None
+                },
+                &mut std::io::empty()
+            )?;
+        } else {
+            f(
+                Entry {
+                    name,
+                    attributes,
+                    hard_link: None
+                },
+                &mut file
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn visit<F>(archive: &Path, format: Format, f: F) -> Result<(), PackError>
+where
+    F: FnMut(Entry, &mut dyn Read) -> Result<(), PackError>
+{
+    let file = File::open(archive)?;
+    match format {
+        Format::Tar => visit_tar(BufReader::new(file), f),
+        Format::TarGz => visit_tar(GzDecoder::new(BufReader::new(file)), f),
+        Format::Zip => visit_zip(file, f)
+    }
+}
+
+//Digests are computed from the packed data so that each entry is read only once
+fn pack_entry(
+    encoder: &mut Package<File>,
+    name: &str,
+    data: &mut dyn Read,
+    hashes: Option<&mut Vec<(String, String)>>
+) -> Result<(), PackError>
+{
+    match hashes {
+        Some(hashes) => {
+            let mut reader = HashReader::new(data);
+            encoder.pack(name, &mut reader)?;
+            hashes.push((name.into(), reader.finish()));
+        },
+        None => encoder.pack(name, data)?
+    }
+    Ok(())
+}
+
+//Returns the hard links of each entry holding their data, a link to another link is resolved to
+//the entry the other link points to
+fn resolve_hard_links(
+    links: Vec<(String, String)>,
+    names: &HashSet<String>
+) -> Result<HashMap<String, Vec<String>>, PackError>
+{
+    let targets: HashMap<&str, &str> = links
+        .iter()
+        .map(|(name, target)| (name.as_str(), target.as_str()))
+        .collect();
+    let mut resolved: HashMap<String, Vec<String>> = HashMap::new();
+    for (name, target) in &links {
+        let mut target = target.as_str();
+        let mut depth = 0;
+        while let Some(next) = targets.get(target) {
+            depth += 1;
+            if depth > links.len() {
+                return Err(PackError::Input(format!(
+                    "hard link '{}' is part of a cycle",
+                    name
+                )));
+            }
+            target = next;
+        }
+        if !names.contains(target) {
+            return Err(PackError::Input(format!(
+                "hard link '{}' points to missing entry '{}'",
+                name, target
+            )));
+        }
+        resolved
+            .entry(target.into())
+            .or_default()
+            .push(name.clone());
+    }
+    Ok(resolved)
+}
+
+/// An archive entry imported as an object.
+pub struct Imported
+{
+    pub name: String,
+    /// Name of the entry a hard link points to; its data is copied to the object.
+    pub copy_of: Option<String>
+}
+
+/// Creates the BPXP `file` from the entries of a tar, tar.gz or zip archive; the input,
+/// dry-run and progress options are ignored.
+///
+/// The archive is read once, unless it contains hard links in which case it is read a second time
+/// to copy the data of the entries they point to. Returns the imported objects, hard links last.
+pub fn import(
+    file: &Path,
+    archive: &Path,
+    options: &PackOptions
+) -> Result<Vec<Imported>, PackError>
+{
+    let format = detect_format(archive).ok_or_else(|| {
+        PackError::Input(format!(
+            "unsupported archive format '{}' (expected .tar, .tar.gz, .tgz or .zip)",
+            archive.display()
+        ))
+    })?;
+    let epoch = options.source_date_epoch;
+    let mut names = HashSet::new();
+    let mut hashes = Vec::new();
+    let mut attributes = Vec::new();
+    let mut hard_links = Vec::new();
+    let mut imported = Vec::new();
+    let out = output::create(file)?;
+    let mut encoder = Package::create(out.reopen()?, create_builder(&options.build)?)?;
+    visit(archive, format, |mut entry, data| {
+        if !names.insert(entry.name.clone()) {
+            return Err(PackError::Input(format!(
+                "archive contains several entries named '{}'",
+                entry.name
+            )));
+        }
+        if let Some(epoch) = epoch {
+            entry.attributes.clamp_mtime(epoch);
+        }
+        match entry.hard_link {
+            Some(target) => hard_links.push((entry.name.clone(), target)),
+            None => {
+                pack_entry(
+                    &mut encoder,
+                    &entry.name,
+                    data,
+                    options.hashes.then_some(&mut hashes)
+                )?;
+                imported.push(Imported {
+                    name: entry.name.clone(),
+                    copy_of: None
+                });
+            }
+        }
+        attributes.push((entry.name, entry.attributes));
+        Ok(())
+    })?;
+    if !hard_links.is_empty() {
+        let hard_links = resolve_hard_links(hard_links, &names)?;
+        visit(archive, format, |entry, data| {
+            let links = match hard_links.get(&entry.name) {
+                Some(v) => v,
+                None => return Ok(())
+            };
+            //Entry data can only be read once from a stream
+            let mut tmp = tempfile::tempfile()?;
+            std::io::copy(data, &mut tmp)?;
+            for name in links {
+                tmp.seek(SeekFrom::Start(0))?;
+                pack_entry(
+                    &mut encoder,
+                    name,
+                    &mut BufReader::new(&tmp),
+                    options.hashes.then_some(&mut hashes)
+                )?;
+                imported.push(Imported {
+                    name: name.clone(),
+                    copy_of: Some(entry.name.clone())
+                });
+            }
+            Ok(())
+        })?;
+    }
+    let mut metadata = match &options.metadata {
+        Some(file) => Some(metadata::load(file)?),
+        None => None
+    };
+    if options.hashes {
+        insert_hashes(metadata.get_or_insert_with(Object::new), hashes);
+    }
+    if options.preserve {
+        insert_attributes(metadata.get_or_insert_with(Object::new), attributes);
+    }
+    //The metadata is written by save, after all objects
+    if let Some(metadata) = metadata {
+        encoder.set_metadata(metadata);
+    }
+    encoder.save()?;
+    drop(encoder);
+    output::persist(out, file)?;
+    Ok(imported)
+}
+
+fn object_name(name: &str, unnamed_count: &mut usize) -> String
+{
+    if name.is_empty() {
+        *unnamed_count += 1;
+        format!("unnamed_file_{}", unnamed_count)
+    } else {
+        name.into()
+    }
+}
+
+fn tar_header(attributes: Option<&Attributes>) -> Header
+{
+    let mut header = Header::new_gnu();
+    header.set_entry_type(EntryType::Regular);
+    header.set_size(0);
+    header.set_mode(attributes.and_then(|v| v.mode).unwrap_or(0o644));
+    header.set_mtime(attributes.and_then(|v| v.mtime).unwrap_or_default().max(0) as u64);
+    header
+}
+
+fn export_tar<T: Read + Seek>(
+    package: &mut Package<T>,
+    out: File,
+    attributes: Option<AttributeTable>
+) -> Result<Vec<Exported>, UnpackError>
+{
+    let hashes = Hashes::read(package.get_metadata()?);
+    let mut builder = tar::Builder::new(out);
+    let mut unnamed_count = 0;
+    let mut exported = Vec::new();
+    for mut v in package.objects()? {
+        let size = v.size();
+        let name = object_name(v.load_name()?, &mut unnamed_count);
+        let attributes = attributes.as_ref().and_then(|v| v.get(&name));
+        let mut header = tar_header(attributes.as_ref());
+        if let Some(target) = attributes.as_ref().and_then(|v| v.link.as_ref()) {
+            header.set_entry_type(EntryType::Symlink);
+            builder.append_link(&mut header, &name, target)?;
+            exported.push(Exported { name, size });
+            continue;
+        }
+        let mut entry = builder.append_writer(&mut header, &name)?;
+        let mut out = HashWriter::new(&mut entry);
+        v.unpack(&mut out)?;
+        let hash = out.finish();
+        entry.finish()?;
+        if let Some(hashes) = &hashes {
+            hashes.check(&name, &hash)?;
+        }
+        exported.push(Exported { name, size });
+    }
+    builder.finish()?;
+    Ok(exported)
+}
+
+fn zip_options(attributes: Option<&Attributes>, size: u64) -> FileOptions
+{
+    let mut options = FileOptions::default().large_file(size >= u32::MAX as u64);
+    if let Some(mode) = attributes.and_then(|v| v.mode) {
+        options = options.unix_permissions(mode);
+    }
+    if let Some(time) = attributes.and_then(|v| v.mtime).and_then(zip_date) {
+        options = options.last_modified_time(time);
+    }
+    options
+}
+
+fn export_zip<T: Read + Seek>(
+    package: &mut Package<T>,
+    out: File,
+    attributes: Option<AttributeTable>
+) -> Result<Vec<Exported>, UnpackError>
+{
+    let hashes = Hashes::read(package.get_metadata()?);
+    let mut zip = ZipWriter::new(out);
+    let mut unnamed_count = 0;
+    let mut exported = Vec::new();
+    for mut v in package.objects()? {
+        let size = v.size();
+        let name = object_name(v.load_name()?, &mut unnamed_count);
+        let attributes = attributes.as_ref().and_then(|v| v.get(&name));
+        let options = zip_options(attributes.as_ref(), size);
+        if let Some(target) = attributes.and_then(|v| v.link) {
+            zip.add_symlink(name.as_str(), target, options)?;
+            exported.push(Exported { name, size });
+            continue;
+        }
+        zip.start_file(name.as_str(), options)?;
+        let mut out = HashWriter::new(&mut zip);
+        v.unpack(&mut out)?;
+        let hash = out.finish();
+        if let Some(hashes) = &hashes {
+            hashes.check(&name, &hash)?;
+        }
+        exported.push(Exported { name, size });
+    }
+    zip.finish()?;
+    Ok(exported)
+}
+
+/// An object written to an archive.
+pub struct Exported
+{
+    pub name: String,
+    pub size: u64
+}
+
+/// Writes all objects of the BPXP `file` to a tar or zip archive chosen by extension and returns
+/// them in package order.
+pub fn export(file: &Path, archive: &Path, preserve: bool) -> Result<Vec<Exported>, UnpackError>
+{
+    let format = match detect_format(archive) {
+        Some(Format::TarGz) | None => {
+            return Err(UnpackError::UnsupportedArchive(
+                archive.display().to_string()
+            ))
+        },
+        Some(v) => v
+    };
+    let mut package = Package::open(BufReader::new(File::open(file)?))?;
+    let attributes = if preserve {
+        AttributeTable::read(package.get_metadata()?)
+    } else {
+        None
+    };
+    let out = File::create(archive)?;
+    match format {
+        Format::Zip => export_zip(&mut package, out, attributes),
+        _ => export_tar(&mut package, out, attributes)
+    }
+}
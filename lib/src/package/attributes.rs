@@ -0,0 +1,221 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    fs::{File, Metadata},
+    path::Path,
+    time::{Duration, UNIX_EPOCH}
+};
+
+use bpx::sd::{Object, Value};
+
+use crate::package::{
+    input::Input,
+    metadata::{self, Table}
+};
+
+/// Name of the metadata key holding the file attributes of each object.
+pub const ATTRIBUTES_KEY: &str = "bpxp.attributes";
+
+/// Permissions, modification time and symbolic link target of an object.
+pub struct Attributes
+{
+    pub mode: Option<u32>,
+    pub mtime: Option<i64>,
+    pub link: Option<String>
+}
+
+//Group and other write permissions are left out as they mostly depend on the umask of the
+//machine which created the file
+#[cfg(unix)]
+fn read_mode(metadata: &Metadata) -> Option<u32>
+{
+    use std::os::unix::fs::PermissionsExt;
+    Some(metadata.permissions().mode() & 0o755)
+}
+
+#[cfg(not(unix))]
+fn read_mode(metadata: &Metadata) -> Option<u32>
+{
+    if metadata.permissions().readonly() {
+        Some(0o444)
+    } else {
+        Some(0o644)
+    }
+}
+
+#[cfg(unix)]
+fn restore_mode(path: &Path, mode: u32) -> std::io::Result<()>
+{
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+fn restore_mode(path: &Path, mode: u32) -> std::io::Result<()>
+{
+    let mut permissions = std::fs::metadata(path)?.permissions();
+    permissions.set_readonly(mode & 0o222 == 0);
+    std::fs::set_permissions(path, permissions)
+}
+
+#[cfg(unix)]
+fn create_link(target: &str, path: &Path) -> std::io::Result<()>
+{
+    std::os::unix::fs::symlink(target, path)
+}
+
+#[cfg(windows)]
+fn create_link(target: &str, path: &Path) -> std::io::Result<()>
+{
+    std::os::windows::fs::symlink_file(target, path)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn create_link(_: &str, _: &Path) -> std::io::Result<()>
+{
+    Err(std::io::ErrorKind::Unsupported.into())
+}
+
+impl Attributes
+{
+    pub fn read(input: &Input) -> std::io::Result<Attributes>
+    {
+        let metadata = std::fs::symlink_metadata(&input.source)?;
+        let mtime = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|v| v.as_secs() as i64)
+            .unwrap_or_default();
+        Ok(Attributes {
+            mode: read_mode(&metadata),
+            mtime: Some(mtime),
+            link: input
+                .link
+                .as_ref()
+                .map(|v| v.to_string_lossy().replace('\\', "/"))
+        })
+    }
+
+    /// Timestamps newer than the given time are replaced by it.
+    pub fn clamp_mtime(&mut self, time: i64)
+    {
+        self.mtime = self.mtime.map(|v| v.min(time));
+    }
+
+    fn from_value(value: &Value) -> Option<Attributes>
+    {
+        let object = match value {
+            Value::Object(v) => v,
+            _ => return None
+        };
+        Some(Attributes {
+            mode: match object.get("mode") {
+                Some(Value::Uint32(v)) => Some(*v),
+                _ => None
+            },
+            mtime: match object.get("mtime") {
+                Some(Value::Int64(v)) => Some(*v),
+                _ => None
+            },
+            link: match object.get("link") {
+                Some(Value::String(v)) => Some(v.clone()),
+                _ => None
+            }
+        })
+    }
+
+    fn to_value(&self) -> Value
+    {
+        let mut values = Vec::new();
+        if let Some(mode) = self.mode {
+            values.push(("mode".into(), Value::Uint32(mode)));
+        }
+        if let Some(mtime) = self.mtime {
+            values.push(("mtime".into(), Value::Int64(mtime)));
+        }
+        if let Some(link) = &self.link {
+            values.push(("link".into(), Value::String(link.clone())));
+        }
+        Value::Object(metadata::build_object(values))
+    }
+
+    /// Returns true if the object is a symbolic link, in which case there is no data to unpack.
+    pub fn restore_link(&self, path: &Path) -> std::io::Result<bool>
+    {
+        match &self.link {
+            Some(target) => {
+                if std::fs::symlink_metadata(path).is_ok() {
+                    std::fs::remove_file(path)?;
+                }
+                create_link(target, path)?;
+                Ok(true)
+            },
+            None => Ok(false)
+        }
+    }
+
+    pub fn restore(&self, path: &Path) -> std::io::Result<()>
+    {
+        if let Some(mtime) = self.mtime {
+            let time = UNIX_EPOCH + Duration::from_secs(mtime.max(0) as u64);
+            File::options().write(true).open(path)?.set_modified(time)?;
+        }
+        //The mode is restored last as it may remove write access to the file
+        if let Some(mode) = self.mode {
+            restore_mode(path, mode)?;
+        }
+        Ok(())
+    }
+}
+
+/// File attributes of each object as stored in the package metadata.
+pub struct AttributeTable(Table);
+
+impl AttributeTable
+{
+    pub fn read(metadata: Option<&Object>) -> Option<AttributeTable>
+    {
+        Table::read(metadata, ATTRIBUTES_KEY).map(AttributeTable)
+    }
+
+    pub fn get(&self, name: &str) -> Option<Attributes>
+    {
+        Attributes::from_value(self.0.get(name)?)
+    }
+}
+
+/// Stores the file attributes of objects in the package metadata.
+pub fn insert_attributes(metadata: &mut Object, attributes: Vec<(String, Attributes)>)
+{
+    let attributes = attributes
+        .into_iter()
+        .map(|(name, attributes)| (name, attributes.to_value()))
+        .collect();
+    Table::insert(metadata, ATTRIBUTES_KEY, attributes);
+}
@@ -0,0 +1,169 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    fs::File,
+    io::{BufReader, Write},
+    path::Path
+};
+
+use bpx::{core::Container, package::Package};
+
+use crate::package::{
+    error::UnpackError,
+    hash::{HashWriter, Hashes},
+    jobs
+};
+
+const PACKAGE_TYPE: u8 = b'P';
+
+struct CountingSink(u64);
+
+impl Write for CountingSink
+{
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize>
+    {
+        self.0 += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()>
+    {
+        Ok(())
+    }
+}
+
+/// Outcome of the verification of a section or an object.
+pub struct Tested
+{
+    /// Name of the object, or "section #N".
+    pub name: String,
+    pub res: Result<(), UnpackError>
+}
+
+//Loading a section checks its checksum; reading it to the end also checks its compressed stream
+fn check_sections(bpx: &mut Container<BufReader<File>>) -> Vec<Tested>
+{
+    let indexes: Vec<u32> = bpx.iter().map(|v| v.index()).collect();
+    let mut tested = Vec::with_capacity(indexes.len());
+
+    for index in indexes {
+        let res = match bpx.find_section_by_index(index) {
+            Some(handle) => bpx
+                .get_mut(handle)
+                .load()
+                .map_err(UnpackError::from)
+                .and_then(|v| std::io::copy(v, &mut std::io::sink()).map_err(UnpackError::from))
+                .map(|_| ()),
+            None => continue
+        };
+        tested.push(Tested {
+            name: format!("section #{}", index),
+            res
+        });
+    }
+    tested
+}
+
+//Each thread decodes a contiguous range of the object table with its own package handle
+fn test_objects(
+    file: &Path,
+    indexes: &[usize],
+    hashes: Option<&Hashes>
+) -> Result<Vec<Tested>, UnpackError>
+{
+    let first = match indexes.first() {
+        Some(v) => *v,
+        None => return Ok(Vec::new())
+    };
+    let mut decoder = Package::open(BufReader::new(File::open(file)?))?;
+    let mut tested = Vec::with_capacity(indexes.len());
+
+    for (i, mut v) in decoder
+        .objects()?
+        .enumerate()
+        .skip(first)
+        .take(indexes.len())
+    {
+        let size = v.size();
+        let name = match v.load_name() {
+            Ok(name) => name.to_string(),
+            Err(e) => {
+                tested.push(Tested {
+                    name: format!("object #{}", i),
+                    res: Err(e.into())
+                });
+                continue;
+            }
+        };
+        let mut sink = CountingSink(0);
+        let mut writer = HashWriter::new(&mut sink);
+        let res = match v.unpack(&mut writer) {
+            Ok(_) => {
+                let hash = writer.finish();
+                if sink.0 != size {
+                    Err(UnpackError::SizeMismatch(size, sink.0))
+                } else if let Some(hashes) = hashes {
+                    hashes.check(&name, &hash)
+                } else {
+                    Ok(())
+                }
+            },
+            Err(e) => Err(e.into())
+        };
+        tested.push(Tested { name, res });
+    }
+    Ok(tested)
+}
+
+fn check_objects(file: &Path, jobs: usize) -> Result<Vec<Tested>, UnpackError>
+{
+    let mut decoder = Package::open(BufReader::new(File::open(file)?))?;
+    let hashes = Hashes::read(decoder.get_metadata()?);
+    let indexes: Vec<usize> = (0..decoder.objects()?.count()).collect();
+    jobs::map_chunks(&indexes, jobs, |chunk| {
+        test_objects(file, chunk, hashes.as_ref())
+    })
+}
+
+/// Verifies all sections of the BPX `file` and decodes all its objects when it is a BPXP, without
+/// writing anything; `jobs` threads decode objects, 0 uses one thread per CPU.
+///
+/// Returns the outcome of each section followed by each object.
+pub fn run(file: &Path, jobs: usize) -> Result<Vec<Tested>, UnpackError>
+{
+    let mut bpx = Container::open(BufReader::new(File::open(file)?))?;
+    let package = bpx.get_main_header().btype == PACKAGE_TYPE;
+    let mut tested = check_sections(&mut bpx);
+    drop(bpx);
+    //Containers of other types have no object table
+    if package {
+        tested.extend(check_objects(file, jobs)?);
+    }
+    Ok(tested)
+}
@@ -0,0 +1,260 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::File,
+    io::{BufReader, Read, Seek},
+    path::Path
+};
+
+use bpx::package::Package;
+use similar::TextDiff;
+
+use crate::package::{
+    error::UnpackError,
+    hash::{HashWriter, Hashes},
+    metadata,
+    pack::{Architecture, Platform},
+    unpack::find_unpack
+};
+
+/// Content change of an object with `unified` set.
+pub enum Patch
+{
+    /// Unified diff of both versions of a text object.
+    Text(String),
+    /// One of the versions is not text.
+    Binary
+}
+
+/// A difference found by [run].
+pub enum Difference
+{
+    /// Architecture, Platform or Generator changed, with the old and new values.
+    Target(&'static str, String, String),
+    MetadataRemoved(String),
+    MetadataAdded(String, serde_json::Value),
+    /// Key with its old and new values.
+    MetadataChanged(String, serde_json::Value, serde_json::Value),
+    Removed(String),
+    Added(String),
+    /// Old and new name of an object whose content is unchanged.
+    Renamed(String, String),
+    /// Object whose content changed, with its old and new sizes.
+    Changed(String, u64, u64, Option<Patch>)
+}
+
+struct ObjectInfo
+{
+    size: u64,
+    hash: String
+}
+
+fn read_objects<T: Read + Seek>(
+    package: &mut Package<T>
+) -> Result<BTreeMap<String, ObjectInfo>, UnpackError>
+{
+    let hashes = Hashes::read(package.get_metadata()?);
+    let mut objects = BTreeMap::new();
+    for mut v in package.objects()? {
+        let size = v.size();
+        let name: String = v.load_name()?.into();
+        //Objects packed without --hashes are decoded to compute their digest
+        let hash = match hashes.as_ref().and_then(|h| h.get(&name)) {
+            Some(hash) => hash.into(),
+            None => {
+                let mut writer = HashWriter::new(std::io::sink());
+                v.unpack(&mut writer)?;
+                writer.finish()
+            }
+        };
+        objects.insert(name, ObjectInfo { size, hash });
+    }
+    Ok(objects)
+}
+
+fn load<T: Read + Seek>(package: &mut Package<T>, name: &str) -> Result<Vec<u8>, UnpackError>
+{
+    let mut data = Vec::new();
+    find_unpack(package, name, &mut data)?;
+    Ok(data)
+}
+
+//Objects containing NUL bytes or invalid UTF-8 are considered binary
+fn as_text(data: &[u8]) -> Option<&str>
+{
+    if data.contains(&0) {
+        None
+    } else {
+        std::str::from_utf8(data).ok()
+    }
+}
+
+fn patch<T: Read + Seek>(
+    old: &mut Package<T>,
+    new: &mut Package<T>,
+    name: &str
+) -> Result<Patch, UnpackError>
+{
+    let old_data = load(old, name)?;
+    let new_data = load(new, name)?;
+    match (as_text(&old_data), as_text(&new_data)) {
+        (Some(old_text), Some(new_text)) => Ok(Patch::Text(
+            TextDiff::from_lines(old_text, new_text)
+                .unified_diff()
+                .header(&format!("a/{}", name), &format!("b/{}", name))
+                .to_string()
+        )),
+        _ => Ok(Patch::Binary)
+    }
+}
+
+fn diff_target<T: Read + Seek>(old: &Package<T>, new: &Package<T>, diffs: &mut Vec<Difference>)
+{
+    let mut compare = |what: &'static str, old: String, new: String| {
+        if old != new {
+            diffs.push(Difference::Target(what, old, new));
+        }
+    };
+    compare(
+        "Architecture",
+        Architecture::from(old.get_architecture()).name().into(),
+        Architecture::from(new.get_architecture()).name().into()
+    );
+    compare(
+        "Platform",
+        Platform::from(old.get_platform()).name().into(),
+        Platform::from(new.get_platform()).name().into()
+    );
+    let generator = |v: [u8; 2]| format!("{}{}", v[0] as char, v[1] as char);
+    compare(
+        "Generator",
+        generator(old.get_type_code()),
+        generator(new.get_type_code())
+    );
+}
+
+//Only user metadata is compared, the keys used by bpx change with the objects
+fn diff_metadata<T: Read + Seek>(
+    old: &mut Package<T>,
+    new: &mut Package<T>,
+    diffs: &mut Vec<Difference>
+) -> Result<(), UnpackError>
+{
+    let old = old
+        .get_metadata()?
+        .map(metadata::user_metadata)
+        .unwrap_or_default();
+    let new = new
+        .get_metadata()?
+        .map(metadata::user_metadata)
+        .unwrap_or_default();
+    for key in old.keys().chain(new.keys()).collect::<BTreeSet<_>>() {
+        match (old.get(key), new.get(key)) {
+            (Some(_), None) => diffs.push(Difference::MetadataRemoved(key.clone())),
+            (None, Some(value)) => {
+                diffs.push(Difference::MetadataAdded(key.clone(), value.clone()))
+            },
+            (Some(a), Some(b)) if a != b => diffs.push(Difference::MetadataChanged(
+                key.clone(),
+                a.clone(),
+                b.clone()
+            )),
+            _ => ()
+        }
+    }
+    Ok(())
+}
+
+/// Compares the objects, metadata and target of two BPXP; the packages are identical when no
+/// difference is returned.
+///
+/// `unified` computes the [Patch] of each changed object.
+pub fn run(old: &Path, new: &Path, unified: bool) -> Result<Vec<Difference>, UnpackError>
+{
+    let mut old = Package::open(BufReader::new(File::open(old)?))?;
+    let mut new = Package::open(BufReader::new(File::open(new)?))?;
+    let mut diffs = Vec::new();
+    diff_target(&old, &new, &mut diffs);
+    diff_metadata(&mut old, &mut new, &mut diffs)?;
+    let old_objects = read_objects(&mut old)?;
+    let new_objects = read_objects(&mut new)?;
+    let mut removed: Vec<&str> = old_objects
+        .keys()
+        .filter(|v| !new_objects.contains_key(*v))
+        .map(|v| v.as_str())
+        .collect();
+    let mut added: Vec<&str> = new_objects
+        .keys()
+        .filter(|v| !old_objects.contains_key(*v))
+        .map(|v| v.as_str())
+        .collect();
+    //A removed object is renamed if an added object has the same content
+    let mut renamed = Vec::new();
+    removed.retain(|name| {
+        let info = &old_objects[*name];
+        let found = added.iter().position(|v| {
+            let other = &new_objects[*v];
+            other.size == info.size && other.hash == info.hash
+        });
+        match found {
+            Some(i) => {
+                renamed.push((*name, added.remove(i)));
+                false
+            },
+            None => true
+        }
+    });
+    diffs.extend(removed.iter().map(|v| Difference::Removed(v.to_string())));
+    diffs.extend(added.iter().map(|v| Difference::Added(v.to_string())));
+    diffs.extend(renamed.iter().map(|(old_name, new_name)| {
+        Difference::Renamed(old_name.to_string(), new_name.to_string())
+    }));
+    for (name, info) in &old_objects {
+        let other = match new_objects.get(name) {
+            Some(v) => v,
+            None => continue
+        };
+        if other.size == info.size && other.hash == info.hash {
+            continue;
+        }
+        let patch = if unified {
+            Some(patch(&mut old, &mut new, name)?)
+        } else {
+            None
+        };
+        diffs.push(Difference::Changed(
+            name.clone(),
+            info.size,
+            other.size,
+            patch
+        ));
+    }
+    Ok(diffs)
+}
@@ -0,0 +1,130 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fmt::{Display, Formatter};
+
+use bpx::macros::impl_err_conversion;
+
+/// Error returned when reading a BPXP.
+pub enum UnpackError
+{
+    Bpxp(bpx::package::error::ReadError),
+    Bpx(bpx::core::error::ReadError),
+    Io(std::io::Error),
+    Strings(bpx::strings::ReadError),
+    ObjectNotFound(String),
+    SizeMismatch(u64, u64),
+    HashMismatch(String),
+    UnsafePath(String),
+    UnsafeLink(String, String),
+    TestFailed(usize),
+    Zip(zip::result::ZipError),
+    UnsupportedArchive(String),
+    BinaryOutput
+}
+
+impl_err_conversion!(
+    UnpackError {
+        bpx::package::error::ReadError => Bpxp,
+        bpx::core::error::ReadError => Bpx,
+        std::io::Error => Io,
+        bpx::strings::ReadError => Strings,
+        zip::result::ZipError => Zip
+    }
+);
+
+/// Error returned when writing a BPXP.
+pub enum PackError
+{
+    Bpxp(bpx::package::error::WriteError),
+    Bpx(bpx::core::error::WriteError),
+    Read(bpx::package::error::ReadError),
+    ReadBpx(bpx::core::error::ReadError),
+    Strings(bpx::strings::ReadError),
+    Io(std::io::Error),
+    Zip(zip::result::ZipError),
+    Input(String),
+    Problems(Vec<String>)
+}
+
+impl_err_conversion!(
+    PackError {
+        bpx::package::error::WriteError => Bpxp,
+        bpx::core::error::WriteError => Bpx,
+        bpx::package::error::ReadError => Read,
+        bpx::core::error::ReadError => ReadBpx,
+        bpx::strings::ReadError => Strings,
+        std::io::Error => Io,
+        zip::result::ZipError => Zip
+    }
+);
+
+impl Display for PackError
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result
+    {
+        match self {
+            PackError::Bpxp(e) => write!(f, "BPXP error: {}", e),
+            PackError::Bpx(e) => write!(f, "BPX error: {}", e),
+            PackError::Read(e) => write!(f, "BPXP error: {}", e),
+            PackError::ReadBpx(e) => write!(f, "BPX error: {}", e),
+            PackError::Strings(e) => write!(f, "Strings error: {}", e),
+            PackError::Io(e) => write!(f, "IO error: {}", e),
+            PackError::Zip(e) => write!(f, "Zip error: {}", e),
+            PackError::Input(s) => write!(f, "Input error: {}", s),
+            PackError::Problems(problems) => {
+                for v in problems {
+                    writeln!(f, "{}", v)?;
+                }
+                write!(f, "{} problem(s) found", problems.len())
+            }
+        }
+    }
+}
+
+impl Display for UnpackError
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result
+    {
+        match self {
+            UnpackError::Bpxp(e) => write!(f, "BPXP error: {}", e),
+            UnpackError::Bpx(e) => write!(f, "BPX error: {}", e),
+            UnpackError::Io(e) => write!(f, "IO error: {}", e),
+            UnpackError::Strings(e) => write!(f, "Strings error: {}", e),
+            UnpackError::ObjectNotFound(name) => write!(f, "Could not find object named '{}'", name),
+            UnpackError::SizeMismatch(expected, actual) => write!(f, "Expected {} byte(s) but decoded {} byte(s)", expected, actual),
+            UnpackError::HashMismatch(name) => write!(f, "SHA-256 of object '{}' does not match the digest stored in the package", name),
+            UnpackError::UnsafePath(name) => write!(f, "Refusing to unpack object '{}' outside of the target directory", name),
+            UnpackError::UnsafeLink(name, link) => write!(f, "Refusing to unpack symbolic link '{}' pointing outside of the target directory ({})", name, link),
+            UnpackError::TestFailed(count) => write!(f, "{} section(s)/object(s) failed verification", count),
+            UnpackError::Zip(e) => write!(f, "Zip error: {}", e),
+            UnpackError::UnsupportedArchive(path) => write!(f, "Unsupported archive format '{}' (expected .tar or .zip)", path),
+            UnpackError::BinaryOutput => f.write_str("Outputing binary data to standard output can mess-up your terminal, please use --force if you're sure to continue")
+        }
+    }
+}
@@ -0,0 +1,150 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::io::{Read, Write};
+
+use bpx::sd::{Object, Value};
+use sha2::{Digest, Sha256};
+
+use crate::package::{error::UnpackError, metadata::Table};
+
+/// Name of the metadata key holding the SHA-256 digest of each object.
+pub const HASHES_KEY: &str = "bpxp.hashes";
+
+fn to_hex(digest: &[u8]) -> String
+{
+    digest.iter().map(|v| format!("{:02x}", v)).collect()
+}
+
+/// Computes the SHA-256 of the data read through it.
+pub struct HashReader<R: Read>
+{
+    inner: R,
+    hasher: Sha256
+}
+
+impl<R: Read> HashReader<R>
+{
+    pub fn new(inner: R) -> HashReader<R>
+    {
+        HashReader {
+            inner,
+            hasher: Sha256::new()
+        }
+    }
+
+    pub fn finish(self) -> String
+    {
+        to_hex(&self.hasher.finalize())
+    }
+}
+
+impl<R: Read> Read for HashReader<R>
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize>
+    {
+        let len = self.inner.read(buf)?;
+        self.hasher.update(&buf[..len]);
+        Ok(len)
+    }
+}
+
+/// Computes the SHA-256 of the data written through it.
+pub struct HashWriter<W: Write>
+{
+    inner: W,
+    hasher: Sha256
+}
+
+impl<W: Write> HashWriter<W>
+{
+    pub fn new(inner: W) -> HashWriter<W>
+    {
+        HashWriter {
+            inner,
+            hasher: Sha256::new()
+        }
+    }
+
+    pub fn finish(self) -> String
+    {
+        to_hex(&self.hasher.finalize())
+    }
+}
+
+impl<W: Write> Write for HashWriter<W>
+{
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize>
+    {
+        let len = self.inner.write(buf)?;
+        self.hasher.update(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()>
+    {
+        self.inner.flush()
+    }
+}
+
+/// SHA-256 digest of each object as stored in the package metadata.
+pub struct Hashes(Table);
+
+impl Hashes
+{
+    pub fn read(metadata: Option<&Object>) -> Option<Hashes>
+    {
+        Table::read(metadata, HASHES_KEY).map(Hashes)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str>
+    {
+        match self.0.get(name) {
+            Some(Value::String(v)) => Some(v),
+            _ => None
+        }
+    }
+
+    pub fn check(&self, name: &str, actual: &str) -> Result<(), UnpackError>
+    {
+        match self.get(name) {
+            Some(expected) if expected != actual => Err(UnpackError::HashMismatch(name.into())),
+            _ => Ok(())
+        }
+    }
+}
+
+/// Stores the digests of objects in the package metadata.
+pub fn insert_hashes(metadata: &mut Object, hashes: Vec<(String, String)>)
+{
+    let hashes = hashes
+        .into_iter()
+        .map(|(name, hash)| (name, Value::String(hash)))
+        .collect();
+    Table::insert(metadata, HASHES_KEY, hashes);
+}
@@ -26,48 +26,38 @@
 // NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
 // SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{
-    borrow::Cow,
-    fs::File,
-    io::{BufReader, Read, Seek},
-    path::{Path, PathBuf}
-};
+use std::{fs::File, io::BufReader, path::Path};
 
 use bpx::package::Package;
 
-use crate::error::UnpackError;
+use crate::package::{
+    error::UnpackError,
+    metadata,
+    pack::{Architecture, Platform}
+};
 
-fn custom_unpack<T: Read + Seek>(
-    package: &mut Package<T>,
-    target: &Path,
-    verbose: bool
-) -> Result<(), UnpackError>
+/// Target, generator and user metadata of a BPXP.
+pub struct Info
 {
-    let mut unnamed_count = 0;
-    for mut v in package.objects()? {
-        let size = v.size();
-        let mut path: Cow<str> = v.load_name()?.into();
-        if path.is_empty() {
-            unnamed_count += 1;
-            path = format!("unnamed_file_{}", unnamed_count).into();
-        }
-        if verbose {
-            println!("Unpacking object name {} with {} byte(s)...", path, size);
-        }
-        let dest: PathBuf = [target, Path::new(path.as_ref())].iter().collect();
-        if let Some(v) = dest.parent() {
-            std::fs::create_dir_all(v)?;
-        }
-        let f = File::create(dest)?;
-        v.unpack(f)?;
-    }
-    Ok(())
+    pub architecture: Architecture,
+    pub platform: Platform,
+    /// 2 ASCII characters identifying the tool which generated the package.
+    pub generator: [u8; 2],
+    /// Metadata of the package without the keys used by bpx itself.
+    pub metadata: serde_json::Map<String, serde_json::Value>
 }
 
-pub fn run(file: &Path, verbose: bool) -> Result<(), UnpackError>
+/// Reads the target, generator and user metadata of the BPXP `file`.
+pub fn read(file: &Path) -> Result<Info, UnpackError>
 {
     let mut decoder = Package::open(BufReader::new(File::open(file)?))?;
-
-    custom_unpack(&mut decoder, Path::new("."), verbose)?;
-    Ok(())
+    Ok(Info {
+        architecture: decoder.get_architecture().into(),
+        platform: decoder.get_platform().into(),
+        generator: decoder.get_type_code(),
+        metadata: decoder
+            .get_metadata()?
+            .map(metadata::user_metadata)
+            .unwrap_or_default()
+    })
 }
@@ -0,0 +1,286 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, Read},
+    path::{Path, PathBuf}
+};
+
+use unicode_normalization::UnicodeNormalization;
+
+use crate::package::error::PackError;
+
+/// A file to pack and the name of its object.
+pub(crate) struct Input
+{
+    pub source: PathBuf,
+    pub name: String,
+    pub link: Option<PathBuf>
+}
+
+impl Input
+{
+    /// Symbolic links are stored as empty objects, their target is kept in the attributes.
+    pub(crate) fn open(&self) -> std::io::Result<Box<dyn Read>>
+    {
+        match self.link {
+            Some(_) => Ok(Box::new(std::io::empty())),
+            None => Ok(Box::new(BufReader::new(File::open(&self.source)?)))
+        }
+    }
+
+    pub(crate) fn size(&self) -> std::io::Result<u64>
+    {
+        match self.link {
+            Some(_) => Ok(0),
+            None => Ok(std::fs::metadata(&self.source)?.len())
+        }
+    }
+}
+
+/// Naming of the objects created from files.
+#[derive(Clone, Default)]
+pub struct InputOptions<'a>
+{
+    /// Virtual directory prepended to the name of each object.
+    pub prefix: Option<&'a str>,
+    /// Name objects relative to this directory instead of using their file name.
+    pub base_dir: Option<&'a Path>,
+    /// Pack the target of symbolic links instead of the links themselves.
+    pub follow_links: bool
+}
+
+fn join_name(parent: &str, child: &str) -> String
+{
+    if parent.is_empty() {
+        child.into()
+    } else {
+        format!("{}/{}", parent, child)
+    }
+}
+
+/// Object names always use '/' as separator regardless of the host platform.
+///
+/// Names are also converted to Unicode NFC (macOS file systems for example return decomposed names).
+pub(crate) fn normalize_name(name: &str) -> Result<String, PackError>
+{
+    let name: String = name.nfc().collect();
+    let mut res = String::new();
+    for component in name.split(|c| c == '/' || c == '\\') {
+        match component {
+            "" | "." => continue,
+            ".." => {
+                return Err(PackError::Input(format!(
+                    "object name '{}' must not contain '..'",
+                    name
+                )))
+            },
+            _ => res = join_name(&res, component)
+        }
+    }
+    if res.is_empty() {
+        return Err(PackError::Input(format!("object name '{}' is empty", name)));
+    }
+    Ok(res)
+}
+
+fn relative_name(source: &Path, base_dir: &Path) -> Result<String, PackError>
+{
+    let relative = match source.strip_prefix(base_dir) {
+        Ok(v) => v.to_path_buf(),
+        Err(_) => {
+            let source = source.canonicalize()?;
+            let base_dir = base_dir.canonicalize()?;
+            match source.strip_prefix(&base_dir) {
+                Ok(v) => v.to_path_buf(),
+                Err(_) => {
+                    return Err(PackError::Input(format!(
+                        "{} is not located under base directory {}",
+                        source.display(),
+                        base_dir.display()
+                    )))
+                },
+            }
+        }
+    };
+    Ok(relative.to_string_lossy().into())
+}
+
+fn default_name(source: &Path, options: &InputOptions) -> Result<String, PackError>
+{
+    if let Some(base_dir) = options.base_dir {
+        return relative_name(source, base_dir);
+    }
+    let name = match source.file_name() {
+        Some(v) => v.to_string_lossy().into(),
+        //Paths such as '.' or '..' do not carry a file name
+        None => match source.canonicalize()?.file_name() {
+            Some(v) => v.to_string_lossy().into(),
+            None => String::new()
+        }
+    };
+    Ok(name)
+}
+
+//`parents` holds the canonical path of the directories being walked, a directory reached again
+//through a followed link would be walked forever
+fn walk(
+    inputs: &mut Vec<Input>,
+    parents: &mut Vec<PathBuf>,
+    source: &Path,
+    name: String,
+    options: &InputOptions
+) -> Result<(), PackError>
+{
+    let is_link = std::fs::symlink_metadata(source)?.file_type().is_symlink();
+    if is_link && !options.follow_links {
+        inputs.push(Input {
+            source: source.into(),
+            name,
+            link: Some(std::fs::read_link(source)?)
+        });
+    } else if source.is_dir() {
+        let dir = source.canonicalize()?;
+        if parents.contains(&dir) {
+            return Err(PackError::Input(format!(
+                "symbolic link loop: {} points to one of its parent directories",
+                source.display()
+            )));
+        }
+        parents.push(dir);
+        //Directory listing order depends on the file system; sort it by object name to produce
+        //the same package on every machine
+        let mut entries = std::fs::read_dir(source)?.collect::<Result<Vec<_>, _>>()?;
+        entries.sort_by_cached_key(|v| v.file_name().to_string_lossy().nfc().collect::<String>());
+        for entry in entries {
+            let child = join_name(&name, &entry.file_name().to_string_lossy());
+            walk(inputs, parents, &entry.path(), child, options)?;
+        }
+        parents.pop();
+    } else {
+        inputs.push(Input {
+            source: source.into(),
+            name,
+            link: None
+        });
+    }
+    Ok(())
+}
+
+/// Splits a src=dest argument, returns None when the argument is a plain path.
+pub(crate) fn parse_mapping(file: &str) -> Option<(&str, &str)>
+{
+    //A path which exists as-is takes precedence over the src=dest syntax
+    if Path::new(file).exists() {
+        return None;
+    }
+    file.split_once('=')
+}
+
+/// Splits a path or path=name argument into a source and an optional object name.
+pub(crate) fn parse_source(file: &str) -> (PathBuf, Option<String>)
+{
+    match parse_mapping(file) {
+        Some((source, dest)) => (PathBuf::from(source), Some(dest.into())),
+        None => (PathBuf::from(file), None)
+    }
+}
+
+/// Expands directories and names the objects of files given as path or path=name.
+pub(crate) fn resolve<'a, I: Iterator<Item = &'a str>>(
+    files: I,
+    options: &InputOptions
+) -> Result<Vec<Input>, PackError>
+{
+    resolve_sources(files.map(parse_source), options)
+}
+
+fn resolve_source(
+    inputs: &mut Vec<Input>,
+    source: &Path,
+    name: Option<String>,
+    options: &InputOptions
+) -> Result<(), PackError>
+{
+    let name = match name {
+        Some(v) => v,
+        None => default_name(source, options)?
+    };
+    let name = match options.prefix {
+        Some(prefix) => join_name(prefix, &name),
+        None => name
+    };
+    walk(inputs, &mut Vec::new(), source, name, options)
+}
+
+/// Objects without an explicit name are named after their source path.
+///
+/// Unreadable files, invalid names and name collisions are all reported at once in a
+/// [PackError::Problems](crate::package::error::PackError::Problems).
+pub(crate) fn resolve_sources<I: Iterator<Item = (PathBuf, Option<String>)>>(
+    sources: I,
+    options: &InputOptions
+) -> Result<Vec<Input>, PackError>
+{
+    let mut inputs = Vec::new();
+    let mut problems = Vec::new();
+
+    for (source, name) in sources {
+        if let Err(e) = resolve_source(&mut inputs, &source, name, options) {
+            problems.push(format!("Cannot pack {}: {}", source.display(), e));
+        }
+    }
+    for v in &mut inputs {
+        match normalize_name(&v.name) {
+            Ok(name) => v.name = name,
+            Err(e) => problems.push(e.to_string())
+        }
+    }
+    let mut names: HashMap<&str, &Path> = HashMap::new();
+    for v in &inputs {
+        if let Some(other) = names.insert(&v.name, &v.source) {
+            problems.push(format!(
+                "Name collision: '{}' is used by both {} and {}",
+                v.name,
+                other.display(),
+                v.source.display()
+            ));
+        }
+        if v.link.is_none() {
+            if let Err(e) = File::open(&v.source) {
+                problems.push(format!("Cannot read {}: {}", v.source.display(), e));
+            }
+        }
+    }
+    if !problems.is_empty() {
+        return Err(PackError::Problems(problems));
+    }
+    Ok(inputs)
+}
@@ -0,0 +1,120 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{collections::BTreeMap, sync::Mutex, thread};
+
+//0 stands for one thread per available CPU
+fn threads(jobs: usize) -> usize
+{
+    match jobs {
+        0 => thread::available_parallelism()
+            .map(|v| v.get())
+            .unwrap_or(1),
+        v => v
+    }
+}
+
+/// Splits items in contiguous chunks each processed on its own thread; results are returned in the
+/// order of the items regardless of which thread finishes first.
+pub fn map_chunks<T, R, E, F>(items: &[T], jobs: usize, f: F) -> Result<Vec<R>, E>
+where
+    T: Sync,
+    R: Send,
+    E: Send,
+    F: Fn(&[T]) -> Result<Vec<R>, E> + Sync
+{
+    let jobs = threads(jobs);
+    if jobs <= 1 || items.len() <= 1 {
+        return f(items);
+    }
+    let f = &f;
+    let results: Vec<Result<Vec<R>, E>> = thread::scope(|s| {
+        let handles: Vec<_> = items
+            .chunks(items.len().div_ceil(jobs))
+            .map(|chunk| s.spawn(move || f(chunk)))
+            .collect();
+        handles.into_iter().map(|v| v.join().unwrap()).collect()
+    });
+    let mut res = Vec::with_capacity(items.len());
+    for v in results {
+        res.extend(v?);
+    }
+    Ok(res)
+}
+
+/// Like [map_chunks] for work which produces no result, such as writing files.
+pub fn for_each_chunk<T, E, F>(items: &[T], jobs: usize, f: F) -> Result<(), E>
+where
+    T: Sync,
+    E: Send,
+    F: Fn(&[T]) -> Result<(), E> + Sync
+{
+    map_chunks(items, jobs, |chunk| f(chunk).map(|_| Vec::<()>::new())).map(|_| ())
+}
+
+struct OrderedState<'a, R>
+{
+    next: usize,
+    pending: BTreeMap<usize, Option<R>>,
+    report: Box<dyn FnMut(R) + Send + 'a>
+}
+
+/// Reports the results of items processed on several threads in the order of the items: a result
+/// is reported as soon as all the previous items are done.
+pub struct Ordered<'a, R>
+{
+    state: Mutex<OrderedState<'a, R>>
+}
+
+impl<'a, R> Ordered<'a, R>
+{
+    pub fn new<F: FnMut(R) + Send + 'a>(report: F) -> Ordered<'a, R>
+    {
+        Ordered {
+            state: Mutex::new(OrderedState {
+                next: 0,
+                pending: BTreeMap::new(),
+                report: Box::new(report)
+            })
+        }
+    }
+
+    /// Marks the item at `index` as done; items without a result are skipped when reporting.
+    pub fn done(&self, index: usize, res: Option<R>)
+    {
+        let mut guard = self.state.lock().unwrap();
+        let state = &mut *guard;
+        state.pending.insert(index, res);
+        while let Some(res) = state.pending.remove(&state.next) {
+            state.next += 1;
+            if let Some(res) = res {
+                (state.report)(res);
+            }
+        }
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{fs::File, io::BufReader, path::Path};
+
+use bpx::{core::Container, package::Package};
+
+use crate::package::{error::UnpackError, hash::Hashes};
+
+//Section type of the object data of a BPXP
+pub(crate) const SECTION_TYPE_DATA: u8 = 0x1;
+
+/// An object of a BPXP as listed by [run].
+#[derive(Clone, Debug)]
+pub struct ListEntry
+{
+    pub name: String,
+    /// Size of the object data.
+    pub size: u64,
+    /// Estimated size of the object once compressed.
+    pub stored: u64,
+    /// Indexes of the data sections holding the object.
+    pub sections: Vec<u32>,
+    /// SHA-256 of the object when requested and stored in the package.
+    pub hash: Option<String>
+}
+
+impl ListEntry
+{
+    /// Ratio of the stored size to the size.
+    pub fn ratio(&self) -> f64
+    {
+        if self.size == 0 {
+            return 1.0;
+        }
+        self.stored as f64 / self.size as f64
+    }
+}
+
+//Index, uncompressed size and compressed size of each data section in file order
+struct DataSections(Vec<(u32, u64, u64)>);
+
+impl DataSections
+{
+    fn read<T>(bpx: &Container<T>) -> DataSections
+    {
+        DataSections(
+            bpx.iter()
+                .filter(|v| v.btype == SECTION_TYPE_DATA)
+                .map(|v| (v.index(), v.size as u64, v.csize as u64))
+                .collect()
+        )
+    }
+
+    //Objects are laid out contiguously across data sections starting at the given section
+    //and offset; the stored size is estimated from the compression ratio of each section
+    fn locate(&self, entry: &mut ListEntry, start: u32, offset: u32)
+    {
+        let mut remaining = entry.size;
+        let mut offset = offset as u64;
+        let first = self.0.iter().position(|(index, _, _)| *index == start);
+        for (index, size, csize) in self.0.iter().skip(first.unwrap_or(self.0.len())) {
+            if remaining == 0 {
+                break;
+            }
+            let len = std::cmp::min(remaining, size.saturating_sub(offset));
+            if *size > 0 {
+                entry.stored += len * csize / size;
+            }
+            entry.sections.push(*index);
+            remaining -= len;
+            offset = 0;
+        }
+    }
+}
+
+/// Formats a size in bytes, using binary units when `human` is set.
+pub fn format_size(size: u64, human: bool) -> String
+{
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+    if !human || size < 1024 {
+        return size.to_string();
+    }
+    let mut value = size as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Formats a size with its unit: binary units when `human` is set, bytes otherwise.
+pub fn format_bytes(size: u64, human: bool) -> String
+{
+    if human {
+        format_size(size, true)
+    } else {
+        format!("{} byte(s)", size)
+    }
+}
+
+//Reads the object table of a BPXP in table order
+fn read_entries(file: &Path, hashes: bool) -> Result<Vec<ListEntry>, UnpackError>
+{
+    let bpx = Container::open(BufReader::new(File::open(file)?))?;
+    let sections = DataSections::read(&bpx);
+    let mut decoder = Package::open(BufReader::new(File::open(file)?))?;
+    let hashes = if hashes {
+        Some(Hashes::read(decoder.get_metadata()?))
+    } else {
+        None
+    };
+    let mut entries = Vec::new();
+
+    for mut v in decoder.objects()? {
+        let header = *v.header();
+        let mut entry = ListEntry {
+            name: v.load_name()?.into(),
+            size: header.size,
+            stored: 0,
+            sections: Vec::new(),
+            hash: None
+        };
+        sections.locate(&mut entry, header.start, header.offset);
+        entries.push(entry);
+    }
+    if let Some(hashes) = &hashes {
+        for entry in &mut entries {
+            entry.hash = hashes
+                .as_ref()
+                .and_then(|v| v.get(&entry.name))
+                .map(Into::into);
+        }
+    }
+    Ok(entries)
+}
+
+/// Order of the objects returned by [run].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Sort
+{
+    Name,
+    /// Decreasing size.
+    Size
+}
+
+/// Options of [run].
+#[derive(Clone, Default)]
+pub struct ListOptions
+{
+    /// Read the SHA-256 of each object stored in the package.
+    pub hashes: bool,
+    /// Sort objects instead of returning them in table order.
+    pub sort: Option<Sort>
+}
+
+/// Returns the objects of a BPXP.
+pub fn run(file: &Path, options: &ListOptions) -> Result<Vec<ListEntry>, UnpackError>
+{
+    let mut entries = read_entries(file, options.hashes)?;
+    match options.sort {
+        Some(Sort::Name) => entries.sort_by(|a, b| a.name.cmp(&b.name)),
+        Some(Sort::Size) => entries.sort_by(|a, b| b.size.cmp(&a.size)),
+        None => ()
+    }
+    Ok(entries)
+}
@@ -0,0 +1,141 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    io::Read,
+    path::{Path, PathBuf}
+};
+
+use serde::Deserialize;
+
+use crate::package::{error::PackError, input::parse_mapping};
+
+/// A file to pack read from a file list or manifest.
+pub struct Entry
+{
+    pub source: PathBuf,
+    pub name: Option<String>
+}
+
+//BPXP compresses whole sections, each holding the data of several objects, so compression can't
+//be chosen per object; the key is only read to reject it with a clear error
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestEntry
+{
+    source: PathBuf,
+    name: Option<String>,
+    compression: Option<String>
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Manifest
+{
+    objects: Vec<ManifestEntry>
+}
+
+//Lists produced by 'find -print0' are NUL-separated so that file names may contain new lines
+fn parse_list(content: &str) -> Vec<Entry>
+{
+    let lines: Vec<&str> = if content.contains('\0') {
+        content.split('\0').collect()
+    } else {
+        content.lines().collect()
+    };
+    lines
+        .into_iter()
+        .filter(|v| !v.is_empty())
+        .map(|line| match parse_mapping(line) {
+            Some((source, dest)) => Entry {
+                source: source.into(),
+                name: Some(dest.into())
+            },
+            None => Entry {
+                source: line.into(),
+                name: None
+            }
+        })
+        .collect()
+}
+
+fn parse_manifest(file: &Path, content: &str, json: bool) -> Result<Vec<Entry>, PackError>
+{
+    let manifest: Result<Manifest, String> = if json {
+        serde_json::from_str(content).map_err(|e| e.to_string())
+    } else {
+        toml::from_str(content).map_err(|e| e.to_string())
+    };
+    let manifest = manifest.map_err(|e| {
+        PackError::Input(format!(
+            "could not parse manifest {} ({})",
+            file.display(),
+            e
+        ))
+    })?;
+    if let Some(v) = manifest.objects.iter().find(|v| v.compression.is_some()) {
+        return Err(PackError::Input(format!(
+            "manifest {} sets the compression of {}, but BPXP only supports compression for the \
+             whole package (use --compression instead)",
+            file.display(),
+            v.source.display()
+        )));
+    }
+    //Relative sources are located from the manifest, which may be used from any directory
+    let dir = file.parent().unwrap_or(Path::new(""));
+    let entries = manifest
+        .objects
+        .into_iter()
+        .map(|v| Entry {
+            source: dir.join(v.source),
+            name: v.name
+        })
+        .collect();
+    Ok(entries)
+}
+
+/// Files with a .toml or .json extension are manifests, anything else (including '-' for standard
+/// input) is a plain list of files.
+///
+/// Relative paths are located from the directory of a manifest and from the current directory in a
+/// plain list.
+pub fn read(list: &str) -> Result<Vec<Entry>, PackError>
+{
+    let mut content = String::new();
+    if list == "-" {
+        std::io::stdin().read_to_string(&mut content)?;
+    } else {
+        content = std::fs::read_to_string(list)?;
+    }
+    let file = Path::new(list);
+    match file.extension().and_then(|v| v.to_str()) {
+        Some("toml") => parse_manifest(file, &content, false),
+        Some("json") => parse_manifest(file, &content, true),
+        _ => Ok(parse_list(&content))
+    }
+}
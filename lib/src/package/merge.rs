@@ -0,0 +1,400 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, Seek, SeekFrom},
+    path::Path
+};
+
+use bpx::{
+    core::{
+        header::{FLAG_CHECK_CRC32, FLAG_CHECK_WEAK, FLAG_COMPRESS_XZ, FLAG_COMPRESS_ZLIB},
+        Container
+    },
+    package::{Builder, Package},
+    sd::{Object, Value}
+};
+use tempfile::NamedTempFile;
+
+use crate::package::{
+    attributes::{insert_attributes, AttributeTable},
+    error::PackError,
+    hash::{insert_hashes, Hashes},
+    list::SECTION_TYPE_DATA,
+    metadata,
+    output,
+    pack::{create_builder, Architecture, BuildOptions, Checksum, Compression, Platform}
+};
+
+/// What to do with objects and metadata keys present in several packages.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Policy
+{
+    /// Fail the merge.
+    #[default]
+    Error,
+    /// Keep the value of the first package.
+    First,
+    /// Keep the value of the last package.
+    Last
+}
+
+/// Options of [run].
+#[derive(Clone, Default)]
+pub struct MergeOptions<'a>
+{
+    /// Target, compression and checksum of the merged package; the target defaults to the one of
+    /// the merged packages, compression and checksum to those of the first package.
+    ///
+    /// The sections of the first package are copied as they are stored when only compression and
+    /// checksum are set and match it, objects of the other packages are always decoded and
+    /// compressed again.
+    pub build: BuildOptions<'a>,
+    pub policy: Policy
+}
+
+/// Objects copied by [run].
+pub struct MergeReport
+{
+    /// Whether the sections of the first package were copied as stored.
+    pub copied: bool,
+    /// Names of the objects decoded and compressed again, with the package providing them.
+    pub recompressed: Vec<(String, String)>
+}
+
+//A BPXP opened for reading
+pub(crate) type Decoder = Package<BufReader<File>>;
+
+fn conflict(what: &str, name: &str, first: &str, second: &str) -> PackError
+{
+    PackError::Input(format!(
+        "{} '{}' exists in both {} and {} (use --on-conflict to choose one)",
+        what, name, first, second
+    ))
+}
+
+//Returns the name of each object of the merged package with the index of the package providing
+//its data
+fn select_objects(
+    packages: &mut [Decoder],
+    files: &[&str],
+    policy: Policy
+) -> Result<Vec<(String, usize)>, PackError>
+{
+    let mut objects: Vec<(String, usize)> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for (i, package) in packages.iter_mut().enumerate() {
+        let mut names = Vec::new();
+        for mut v in package.objects()? {
+            names.push(String::from(v.load_name()?));
+        }
+        for name in names {
+            match positions.get(&name) {
+                None => {
+                    positions.insert(name.clone(), objects.len());
+                    objects.push((name, i));
+                },
+                Some(&pos) => match policy {
+                    Policy::Error => {
+                        return Err(conflict("object", &name, files[objects[pos].1], files[i]))
+                    },
+                    Policy::First => (),
+                    Policy::Last => objects[pos].1 = i
+                }
+            }
+        }
+    }
+    Ok(objects)
+}
+
+/// Creates the builder of a package made from `packages`.
+///
+/// The target of the merged package defaults to the one of the merged packages when they all agree.
+pub(crate) fn target_builder(
+    packages: &[Decoder],
+    files: &[&str],
+    options: &BuildOptions
+) -> Result<Builder, PackError>
+{
+    let mut builder = create_builder(options)?;
+    let first = &packages[0];
+    if options.generator.is_none() {
+        builder = builder.with_type(first.get_type_code());
+    }
+    if options.arch.is_none() {
+        let arch = Architecture::from(first.get_architecture());
+        if let Some(i) = packages
+            .iter()
+            .position(|v| Architecture::from(v.get_architecture()) != arch)
+        {
+            return Err(PackError::Input(format!(
+                "{} and {} target different architectures (use --arch to choose one)",
+                files[0], files[i]
+            )));
+        }
+        builder = builder.with_architecture(first.get_architecture());
+    }
+    if options.platform.is_none() {
+        let platform = Platform::from(first.get_platform());
+        if let Some(i) = packages
+            .iter()
+            .position(|v| Platform::from(v.get_platform()) != platform)
+        {
+            return Err(PackError::Input(format!(
+                "{} and {} target different platforms (use --platform to choose one)",
+                files[0], files[i]
+            )));
+        }
+        builder = builder.with_platform(first.get_platform());
+    }
+    Ok(builder)
+}
+
+fn section_compression(flags: u8) -> Option<Compression>
+{
+    if flags & FLAG_COMPRESS_XZ == FLAG_COMPRESS_XZ {
+        Some(Compression::Xz)
+    } else if flags & FLAG_COMPRESS_ZLIB == FLAG_COMPRESS_ZLIB {
+        Some(Compression::Zlib)
+    } else {
+        None
+    }
+}
+
+fn section_checksum(flags: u8) -> Checksum
+{
+    if flags & FLAG_CHECK_CRC32 == FLAG_CHECK_CRC32 {
+        Checksum::Crc32
+    } else if flags & FLAG_CHECK_WEAK == FLAG_CHECK_WEAK {
+        Checksum::Weak
+    } else {
+        Checksum::None
+    }
+}
+
+/// Fills the compression and checksum left unset in `options` with those of the data sections of
+/// the BPXP `file`, and tells whether these sections can be copied as they are stored.
+///
+/// Sections can't be copied when their settings differ from the requested ones, or when options
+/// which can't be read back from the sections (target, level, threshold) are set.
+pub(crate) fn stored_build<'a>(
+    file: &Path,
+    options: &BuildOptions<'a>
+) -> Result<(BuildOptions<'a>, bool), PackError>
+{
+    let bpx = Container::open(BufReader::new(File::open(file)?))?;
+    let mut compression = None;
+    let mut checksum = None;
+    let mut consistent = true;
+    for v in bpx.iter().filter(|v| v.btype == SECTION_TYPE_DATA) {
+        //Sections under the compression threshold are stored uncompressed whatever the compression
+        if let Some(method) = section_compression(v.flags) {
+            consistent &= compression.replace(method).is_none_or(|v| v == method);
+        }
+        let method = section_checksum(v.flags);
+        consistent &= checksum.replace(method).is_none_or(|v| v == method);
+    }
+    if !consistent {
+        return Ok((options.clone(), false));
+    }
+    let mut build = options.clone();
+    build.compression = build.compression.or(compression);
+    build.checksum = build.checksum.or(checksum);
+    let copy = build.compression == compression
+        && build.checksum == checksum
+        && build.generator.is_none()
+        && build.arch.is_none()
+        && build.platform.is_none()
+        && build.level.is_none()
+        && build.threshold.is_none();
+    Ok((build, copy))
+}
+
+/// Starts the package `out` as a copy of the BPXP `file` (opened as `package`) without the objects
+/// missing from `keep`; kept objects are not decoded and keep their compressed sections.
+pub(crate) fn open_copy(
+    out: &NamedTempFile,
+    package: &mut Decoder,
+    file: &Path,
+    keep: &[&str]
+) -> Result<Package<File>, PackError>
+{
+    std::io::copy(&mut File::open(file)?, &mut out.reopen()?)?;
+    let mut encoder = Package::open(out.reopen()?)?;
+    for mut v in package.objects()? {
+        let name = String::from(v.load_name()?);
+        if !keep.contains(&name.as_str()) {
+            encoder.remove_object(&name)?;
+        }
+    }
+    Ok(encoder)
+}
+
+/// Merges the metadata of `packages` for the objects given with the index of their package.
+///
+/// User metadata keys follow the same conflict policy as objects, digests and attributes are taken
+/// from the package providing each object.
+pub(crate) fn collect_metadata(
+    packages: &mut [Decoder],
+    files: &[&str],
+    objects: &[(String, usize)],
+    policy: Policy
+) -> Result<Option<Object>, PackError>
+{
+    let mut user: Vec<(String, Value, usize)> = Vec::new();
+    let mut hashes = Vec::new();
+    let mut attributes = Vec::new();
+    for (i, package) in packages.iter_mut().enumerate() {
+        let metadata = match package.get_metadata()? {
+            Some(v) => v,
+            None => continue
+        };
+        for (name, value) in metadata::entries(metadata) {
+            if metadata::is_reserved(&name) {
+                continue;
+            }
+            match user.iter_mut().find(|(v, _, _)| *v == name) {
+                None => user.push((name, value.clone(), i)),
+                Some((_, existing, owner)) => {
+                    if metadata::same_value(existing, value) {
+                        continue;
+                    }
+                    match policy {
+                        Policy::Error => {
+                            return Err(conflict("metadata key", &name, files[*owner], files[i]))
+                        },
+                        Policy::First => (),
+                        Policy::Last => {
+                            *existing = value.clone();
+                            *owner = i;
+                        }
+                    }
+                }
+            }
+        }
+        let package_hashes = Hashes::read(Some(metadata));
+        let package_attributes = AttributeTable::read(Some(metadata));
+        for (name, _) in objects.iter().filter(|(_, owner)| *owner == i) {
+            if let Some(hash) = package_hashes.as_ref().and_then(|v| v.get(name)) {
+                hashes.push((name.clone(), hash.into()));
+            }
+            if let Some(attrs) = package_attributes.as_ref().and_then(|v| v.get(name)) {
+                attributes.push((name.clone(), attrs));
+            }
+        }
+    }
+    if user.is_empty() && hashes.is_empty() && attributes.is_empty() {
+        return Ok(None);
+    }
+    let mut metadata =
+        metadata::build_object(user.into_iter().map(|(name, value, _)| (name, value)));
+    if !hashes.is_empty() {
+        insert_hashes(&mut metadata, hashes);
+    }
+    if !attributes.is_empty() {
+        insert_attributes(&mut metadata, attributes);
+    }
+    Ok(Some(metadata))
+}
+
+/// Copies the `selected` objects of a package to `encoder`; the objects are decoded and compressed
+/// again with the settings of `encoder`.
+///
+/// Returns the names of the copied objects.
+pub(crate) fn copy_objects(
+    encoder: &mut Package<File>,
+    package: &mut Decoder,
+    selected: &[&str]
+) -> Result<Vec<String>, PackError>
+{
+    let mut copied = Vec::new();
+    for mut v in package.objects()? {
+        let name = String::from(v.load_name()?);
+        if !selected.contains(&name.as_str()) {
+            continue;
+        }
+        //The BPXP encoder only accepts decoded object data, so objects are streamed through a
+        //temporary file rather than held in memory
+        let mut tmp = tempfile::tempfile()?;
+        v.unpack(&mut tmp)?;
+        tmp.seek(SeekFrom::Start(0))?;
+        encoder.pack(&name, BufReader::new(tmp))?;
+        copied.push(name);
+    }
+    Ok(copied)
+}
+
+/// Writes the objects and metadata of the BPXP `files` to the BPXP `file`.
+pub fn run(file: &Path, files: &[&str], options: &MergeOptions) -> Result<MergeReport, PackError>
+{
+    if files.is_empty() {
+        return Err(PackError::Input("no packages to merge".into()));
+    }
+    let policy = options.policy;
+    let mut packages = Vec::with_capacity(files.len());
+    for v in files {
+        packages.push(Package::open(BufReader::new(File::open(v)?))?);
+    }
+    let objects = select_objects(&mut packages, files, policy)?;
+    let (build, copy) = stored_build(Path::new(files[0]), &options.build)?;
+    let builder = target_builder(&packages, files, &build)?;
+    let metadata = collect_metadata(&mut packages, files, &objects, policy)?;
+    let selected = |i: usize| -> Vec<&str> {
+        objects
+            .iter()
+            .filter(|(_, owner)| *owner == i)
+            .map(|(name, _)| name.as_str())
+            .collect()
+    };
+    let out = output::create(file)?;
+    let mut encoder = if copy {
+        open_copy(&out, &mut packages[0], Path::new(files[0]), &selected(0))?
+    } else {
+        Package::create(out.reopen()?, builder)?
+    };
+
+    let mut recompressed = Vec::new();
+    let first = usize::from(copy);
+    for (i, package) in packages.iter_mut().enumerate().skip(first) {
+        let copied = copy_objects(&mut encoder, package, &selected(i))?;
+        recompressed.extend(copied.into_iter().map(|name| (name, files[i].to_string())));
+    }
+    //The metadata is written by save, after all objects
+    if let Some(metadata) = metadata {
+        encoder.set_metadata(metadata);
+    }
+    encoder.save()?;
+    drop(encoder);
+    output::persist(out, file)?;
+    Ok(MergeReport {
+        copied: copy,
+        recompressed
+    })
+}
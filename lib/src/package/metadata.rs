@@ -0,0 +1,263 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{fs::File, io::BufReader, path::Path};
+
+use bpx::sd::{Array, DebugSymbols, Object, Value};
+
+use crate::package::error::PackError;
+
+fn value_from_json(value: &serde_json::Value) -> Value
+{
+    match value {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(v) => Value::Bool(*v),
+        serde_json::Value::Number(v) => {
+            if let Some(v) = v.as_i64() {
+                Value::Int64(v)
+            } else if let Some(v) = v.as_u64() {
+                Value::Uint64(v)
+            } else {
+                Value::Double(v.as_f64().unwrap_or_default())
+            }
+        },
+        serde_json::Value::String(v) => Value::String(v.clone()),
+        serde_json::Value::Array(v) => {
+            let mut array = Array::new();
+            for item in v {
+                array.add(value_from_json(item));
+            }
+            Value::Array(array)
+        },
+        serde_json::Value::Object(v) => Value::Object(object_from_json(v))
+    }
+}
+
+/// Creates a BPXSD object with debug symbols so that key names can be displayed.
+pub(crate) fn build_object<I: IntoIterator<Item = (String, Value)>>(values: I) -> Object
+{
+    let mut object = Object::new();
+    let mut symbols = DebugSymbols::new();
+    for (name, value) in values {
+        object.set(&name, value);
+        symbols.push(&name);
+    }
+    symbols.write(&mut object);
+    object
+}
+
+/// Sets a key of a BPXSD object keeping its debug symbols up to date.
+pub(crate) fn set(object: &mut Object, name: &str, value: Value)
+{
+    let mut symbols = DebugSymbols::read(object).unwrap_or_else(|_| DebugSymbols::new());
+    object.set(name, value);
+    symbols.push(name);
+    symbols.write(object);
+}
+
+//BPXSD objects and arrays hold at most 255 entries and the debug symbols take one of them
+const TABLE_CHUNK_SIZE: usize = 254;
+const TABLE_ARRAY_SIZE: usize = 255;
+
+/// Table of values keyed by object name, stored in the package metadata.
+///
+/// A package may contain more objects than a single BPXSD object can hold, so the table is
+/// stored as a tree of arrays whose leaves are objects of at most 254 names.
+pub(crate) struct Table(Vec<Object>);
+
+impl Table
+{
+    fn collect(value: &Value, chunks: &mut Vec<Object>)
+    {
+        match value {
+            Value::Object(v) => chunks.push(v.clone()),
+            Value::Array(v) => {
+                for i in 0..v.len() {
+                    Table::collect(&v[i], chunks);
+                }
+            },
+            _ => ()
+        }
+    }
+
+    /// Reads the table stored under the given metadata key.
+    pub fn read(metadata: Option<&Object>, key: &str) -> Option<Table>
+    {
+        let value = metadata?.get(key)?;
+        match value {
+            Value::Object(_) | Value::Array(_) => {
+                let mut chunks = Vec::new();
+                Table::collect(value, &mut chunks);
+                Some(Table(chunks))
+            },
+            _ => None
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value>
+    {
+        self.0.iter().find_map(|v| v.get(name))
+    }
+
+    /// Stores the table under the given metadata key.
+    pub fn insert(metadata: &mut Object, key: &str, values: Vec<(String, Value)>)
+    {
+        let mut nodes = Vec::new();
+        let mut values = values.into_iter().peekable();
+        while values.peek().is_some() {
+            let chunk = build_object(values.by_ref().take(TABLE_CHUNK_SIZE));
+            nodes.push(Value::Object(chunk));
+        }
+        while nodes.len() > TABLE_ARRAY_SIZE {
+            nodes = nodes
+                .chunks(TABLE_ARRAY_SIZE)
+                .map(|v| {
+                    let mut array = Array::new();
+                    for node in v {
+                        array.add(node.clone());
+                    }
+                    Value::Array(array)
+                })
+                .collect();
+        }
+        let mut array = Array::new();
+        for node in nodes {
+            array.add(node);
+        }
+        set(metadata, key, Value::Array(array));
+    }
+}
+
+/// Keys used by bpxp itself are displayed by dedicated options rather than as user metadata.
+pub(crate) fn is_reserved(name: &str) -> bool
+{
+    name.starts_with("bpxp.")
+}
+
+fn object_from_json(map: &serde_json::Map<String, serde_json::Value>) -> Object
+{
+    build_object(
+        map.iter()
+            .map(|(name, value)| (name.clone(), value_from_json(value)))
+    )
+}
+
+/// Loads a JSON file as a BPXSD object.
+pub(crate) fn load(file: &Path) -> Result<Object, PackError>
+{
+    let json: serde_json::Value = match serde_json::from_reader(BufReader::new(File::open(file)?)) {
+        Ok(v) => v,
+        Err(e) => {
+            return Err(PackError::Input(format!(
+                "could not parse metadata file {} ({})",
+                file.display(),
+                e
+            )))
+        },
+    };
+    match &json {
+        serde_json::Value::Object(v) => Ok(object_from_json(v)),
+        _ => Err(PackError::Input(format!(
+            "metadata file {} must contain a JSON object",
+            file.display()
+        )))
+    }
+}
+
+fn value_to_json(value: &Value) -> serde_json::Value
+{
+    match value {
+        Value::Null => serde_json::Value::Null,
+        Value::Bool(v) => (*v).into(),
+        Value::Uint8(v) => (*v).into(),
+        Value::Uint16(v) => (*v).into(),
+        Value::Uint32(v) => (*v).into(),
+        Value::Uint64(v) => (*v).into(),
+        Value::Int8(v) => (*v).into(),
+        Value::Int16(v) => (*v).into(),
+        Value::Int32(v) => (*v).into(),
+        Value::Int64(v) => (*v).into(),
+        Value::Float(v) => (*v).into(),
+        Value::Double(v) => (*v).into(),
+        Value::String(v) => v.clone().into(),
+        Value::Array(v) => {
+            serde_json::Value::Array((0..v.len()).map(|i| value_to_json(&v[i])).collect())
+        },
+        Value::Object(v) => to_json(v)
+    }
+}
+
+/// Returns the keys of a BPXSD object with their value, using the debug symbols for key names.
+pub(crate) fn entries(object: &Object) -> Vec<(String, &Value)>
+{
+    //Objects written without debug symbols can only be displayed using their key hashes
+    let symbols = DebugSymbols::read(object).ok();
+    let debug_key = bpx::utils::hash("__debug__");
+    let mut entries = Vec::new();
+    for key in object.get_keys() {
+        //The debug layer is an implementation detail of BPXSD; its content is used for key names
+        if *key == debug_key {
+            continue;
+        }
+        let name = match symbols.as_ref().and_then(|v| v.lookup(*key)) {
+            Some(name) => name.into(),
+            None => key.to_string()
+        };
+        entries.push((name, &object[*key]));
+    }
+    entries
+}
+
+/// Returns true if both values have the same JSON representation.
+pub(crate) fn same_value(a: &Value, b: &Value) -> bool
+{
+    value_to_json(a) == value_to_json(b)
+}
+
+/// Converts a BPXSD object to JSON.
+pub fn to_json(object: &Object) -> serde_json::Value
+{
+    serde_json::Value::Object(
+        entries(object)
+            .into_iter()
+            .map(|(name, value)| (name, value_to_json(value)))
+            .collect()
+    )
+}
+
+/// Converts the metadata of a package to JSON leaving out the keys used by bpxp itself.
+pub(crate) fn user_metadata(object: &Object) -> serde_json::Map<String, serde_json::Value>
+{
+    match to_json(object) {
+        serde_json::Value::Object(mut map) => {
+            map.retain(|name, _| !is_reserved(name));
+            map
+        },
+        _ => serde_json::Map::new()
+    }
+}
@@ -26,21 +26,26 @@
 // NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
 // SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{fs::File, io::BufReader, path::Path};
+//! Creation, extraction and edition of BPX type P (Package) files.
 
-use bpx::package::Package;
+pub mod archive;
+mod attributes;
+pub mod check;
+pub mod diff;
+pub mod error;
+mod hash;
+pub mod info;
+pub mod input;
+mod jobs;
+pub mod list;
+mod manifest;
+pub mod merge;
+pub mod metadata;
+mod output;
+pub mod pack;
+mod progress;
+pub mod split;
+pub mod unpack;
+pub mod update;
 
-use crate::error::UnpackError;
-
-pub fn run(file: &Path) -> Result<(), UnpackError>
-{
-    let mut decoder = Package::open(BufReader::new(File::open(file)?))?;
-
-    println!("Decoding object table:");
-    for mut v in decoder.objects()? {
-        let size = v.size();
-        let name = v.load_name()?;
-        println!("Name = '{}', Size = {} byte(s)", name, size);
-    }
-    Ok(())
-}
+pub use progress::ProgressMode;
@@ -28,36 +28,59 @@
 
 use std::path::Path;
 
-use clap::clap_app;
+use tempfile::NamedTempFile;
 
-mod bpxinfo;
-mod error;
-mod printsd;
-mod type_ext_maps;
+fn parent_dir(path: &Path) -> &Path
+{
+    match path.parent() {
+        Some(v) if !v.as_os_str().is_empty() => v,
+        _ => Path::new(".")
+    }
+}
+
+#[cfg(unix)]
+fn new_temp(dir: &Path) -> std::io::Result<NamedTempFile>
+{
+    use std::os::unix::fs::PermissionsExt;
+    //Same permissions as File::create, the umask still applies
+    tempfile::Builder::new()
+        .permissions(std::fs::Permissions::from_mode(0o666))
+        .tempfile_in(dir)
+}
+
+#[cfg(not(unix))]
+fn new_temp(dir: &Path) -> std::io::Result<NamedTempFile>
+{
+    NamedTempFile::new_in(dir)
+}
+
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> std::io::Result<()>
+{
+    std::fs::File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_: &Path) -> std::io::Result<()>
+{
+    Ok(())
+}
+
+/// Packages are written to a temporary file in the directory of their destination, so that a
+/// failure never leaves a truncated package behind nor damages the previous one.
+pub fn create(path: &Path) -> std::io::Result<NamedTempFile>
+{
+    new_temp(parent_dir(path))
+}
 
-fn main()
+/// Must only be called once the package is saved and closed.
+pub fn persist(out: NamedTempFile, path: &Path) -> std::io::Result<()>
 {
-    let matches = clap_app!(bpxdump =>
-        (version: "1.0")
-        (author: "BlockProject3D <https://github.com/BlockProject3D>")
-        (about: "Dumps content of a given BPX file")
-        (@arg file: -f --file +required +takes_value "Path to the BPX file to debug")
-        (@arg sht: -s --sht "Prints the section header table (SHT)")
-        (@arg metadata: -m --metadata "Prints metadata (metadata here refers to the TypeExt block)")
-        (@arg hex: -x --hex "Prints data in hex")
-        (@arg force: --force "Force prints data to terminal ignoring potential terminal destruction")
-        (@arg section_id: -d --dump +takes_value "Dumps the content of the section identified by the given index")
-        (@arg out_file: -o --output +takes_value "Save dump output to a file")
-        (@arg bpxsd: --bpxsd "Parse the section to print (specified in -d) as a BPX Structured Data Object (BPXSD)")
-    )
-    .get_matches();
-    let file = matches.value_of("file").unwrap();
-
-    match bpxinfo::run(Path::new(file), &matches) {
-        Ok(()) => std::process::exit(0),
-        Err(e) => {
-            eprintln!("{}", e);
-            std::process::exit(1)
-        }
+    out.as_file().sync_all()?;
+    //A replaced package keeps its permissions
+    if let Ok(metadata) = std::fs::metadata(path) {
+        out.as_file().set_permissions(metadata.permissions())?;
     }
+    out.persist(path).map_err(|e| e.error)?;
+    sync_dir(parent_dir(path))
 }
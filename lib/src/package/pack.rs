@@ -0,0 +1,424 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::path::{Path, PathBuf};
+
+use bpx::{
+    core::options::{Checksum as BpxChecksum, CompressionMethod},
+    package::{Architecture as BpxArchitecture, Builder, Package, Platform as BpxPlatform},
+    sd::Object
+};
+
+use crate::package::{
+    attributes::{insert_attributes, Attributes},
+    error::PackError,
+    hash::{insert_hashes, HashReader},
+    input::{parse_source, resolve_sources, Input, InputOptions},
+    manifest,
+    metadata,
+    output,
+    progress::{Progress, ProgressMode, ProgressReader}
+};
+
+/// Target architecture of a BPXP.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Architecture
+{
+    X86_64,
+    Aarch64,
+    X86,
+    Armv7hl,
+    Any
+}
+
+impl Architecture
+{
+    /// Display name of the architecture.
+    pub fn name(self) -> &'static str
+    {
+        match self {
+            Architecture::X86_64 => "x86_64",
+            Architecture::Aarch64 => "aarch64",
+            Architecture::X86 => "x86",
+            Architecture::Armv7hl => "armv7hl",
+            Architecture::Any => "Any"
+        }
+    }
+
+    fn to_bpx(self) -> BpxArchitecture
+    {
+        match self {
+            Architecture::X86_64 => BpxArchitecture::X86_64,
+            Architecture::Aarch64 => BpxArchitecture::Aarch64,
+            Architecture::X86 => BpxArchitecture::X86,
+            Architecture::Armv7hl => BpxArchitecture::Armv7hl,
+            Architecture::Any => BpxArchitecture::Any
+        }
+    }
+}
+
+impl From<BpxArchitecture> for Architecture
+{
+    fn from(arch: BpxArchitecture) -> Self
+    {
+        match arch {
+            BpxArchitecture::X86_64 => Architecture::X86_64,
+            BpxArchitecture::Aarch64 => Architecture::Aarch64,
+            BpxArchitecture::X86 => Architecture::X86,
+            BpxArchitecture::Armv7hl => Architecture::Armv7hl,
+            BpxArchitecture::Any => Architecture::Any
+        }
+    }
+}
+
+/// Target platform of a BPXP.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Platform
+{
+    Linux,
+    Mac,
+    Windows,
+    Android,
+    Any
+}
+
+impl Platform
+{
+    /// Display name of the platform.
+    pub fn name(self) -> &'static str
+    {
+        match self {
+            Platform::Linux => "Linux",
+            Platform::Mac => "Mac",
+            Platform::Windows => "Windows",
+            Platform::Android => "Android",
+            Platform::Any => "Any"
+        }
+    }
+
+    fn to_bpx(self) -> BpxPlatform
+    {
+        match self {
+            Platform::Linux => BpxPlatform::Linux,
+            Platform::Mac => BpxPlatform::Mac,
+            Platform::Windows => BpxPlatform::Windows,
+            Platform::Android => BpxPlatform::Android,
+            Platform::Any => BpxPlatform::Any
+        }
+    }
+}
+
+impl From<BpxPlatform> for Platform
+{
+    fn from(platform: BpxPlatform) -> Self
+    {
+        match platform {
+            BpxPlatform::Linux => Platform::Linux,
+            BpxPlatform::Mac => Platform::Mac,
+            BpxPlatform::Windows => Platform::Windows,
+            BpxPlatform::Android => Platform::Android,
+            BpxPlatform::Any => Platform::Any
+        }
+    }
+}
+
+/// Compression of the sections of a BPXP.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Compression
+{
+    None,
+    Zlib,
+    Xz
+}
+
+impl Compression
+{
+    fn to_bpx(self) -> Option<CompressionMethod>
+    {
+        match self {
+            Compression::None => None,
+            Compression::Zlib => Some(CompressionMethod::Zlib),
+            Compression::Xz => Some(CompressionMethod::Xz)
+        }
+    }
+}
+
+/// Checksum of the sections of a BPXP.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Checksum
+{
+    None,
+    Weak,
+    Crc32
+}
+
+impl Checksum
+{
+    fn to_bpx(self) -> Option<BpxChecksum>
+    {
+        match self {
+            Checksum::None => None,
+            Checksum::Weak => Some(BpxChecksum::Weak),
+            Checksum::Crc32 => Some(BpxChecksum::Crc32)
+        }
+    }
+}
+
+fn parse_generator(generator: &str) -> Result<[u8; 2], PackError>
+{
+    match generator.as_bytes() {
+        [a, b] if generator.is_ascii() => Ok([*a, *b]),
+        _ => Err(PackError::Input(format!(
+            "generator '{}' must be exactly 2 ASCII characters",
+            generator
+        )))
+    }
+}
+
+/// Target, compression and checksum of a new BPXP; unset fields use the defaults of the BPXP
+/// encoder.
+#[derive(Clone, Default)]
+pub struct BuildOptions<'a>
+{
+    /// 2 ASCII characters identifying the tool which generated the package (defaults to BD).
+    pub generator: Option<&'a str>,
+    pub arch: Option<Architecture>,
+    pub platform: Option<Platform>,
+    pub compression: Option<Compression>,
+    /// Level passed to the compression algorithm.
+    pub level: Option<u32>,
+    /// Size in bytes under which section data is stored uncompressed.
+    pub threshold: Option<u32>,
+    pub checksum: Option<Checksum>
+}
+
+/// Options of [run], most of them also apply to [import](crate::package::archive::import).
+#[derive(Clone)]
+pub struct PackOptions<'a>
+{
+    pub build: BuildOptions<'a>,
+    /// Naming of the packed files.
+    pub input: InputOptions<'a>,
+    /// File list or TOML/JSON manifest of additional files to pack (- for standard input).
+    pub files_from: Option<&'a str>,
+    /// JSON file containing the metadata object to embed.
+    pub metadata: Option<&'a Path>,
+    /// Store the SHA-256 of each object.
+    pub hashes: bool,
+    /// Store file permissions, modification times and symbolic links.
+    pub preserve: bool,
+    /// Clamp stored modification times to this UNIX timestamp.
+    pub source_date_epoch: Option<i64>,
+    /// Resolve the files to pack without writing the package.
+    pub dry_run: bool,
+    pub progress: ProgressMode
+}
+
+impl<'a> Default for PackOptions<'a>
+{
+    fn default() -> Self
+    {
+        PackOptions {
+            build: BuildOptions::default(),
+            input: InputOptions::default(),
+            files_from: None,
+            metadata: None,
+            hashes: false,
+            preserve: true,
+            source_date_epoch: None,
+            dry_run: false,
+            progress: ProgressMode::default()
+        }
+    }
+}
+
+/// A file packed (or which would be packed) as an object.
+pub struct Packed
+{
+    pub source: PathBuf,
+    pub name: String,
+    pub size: u64
+}
+
+/// Creates the BPXP builder for the given target, compression and checksum.
+pub(crate) fn create_builder(options: &BuildOptions) -> Result<Builder, PackError>
+{
+    let mut builder = Builder::new().with_type(parse_generator(options.generator.unwrap_or("BD"))?);
+    if let Some(arch) = options.arch {
+        builder = builder.with_architecture(arch.to_bpx());
+    }
+    if let Some(platform) = options.platform {
+        builder = builder.with_platform(platform.to_bpx());
+    }
+    if let Some(compression) = options.compression {
+        builder = builder.with_compression(compression.to_bpx());
+    }
+    if let Some(level) = options.level {
+        builder = builder.with_compression_level(level);
+    }
+    if let Some(threshold) = options.threshold {
+        builder = builder.with_compression_threshold(threshold);
+    }
+    if let Some(checksum) = options.checksum {
+        builder = builder.with_checksum(checksum.to_bpx());
+    }
+    Ok(builder)
+}
+
+/// Reads the attributes stored for a packed file.
+pub(crate) fn read_attributes(
+    input: &Input,
+    source_date_epoch: Option<i64>
+) -> Result<Attributes, PackError>
+{
+    let mut attributes = Attributes::read(input)?;
+    //Modification times change with every checkout of the files, reproducible builds clamp them
+    //to a source date epoch
+    if let Some(epoch) = source_date_epoch {
+        attributes.clamp_mtime(epoch);
+    }
+    Ok(attributes)
+}
+
+fn create_metadata(
+    options: &PackOptions,
+    inputs: &[Input],
+    digests: Vec<String>
+) -> Result<Option<Object>, PackError>
+{
+    let mut metadata = match &options.metadata {
+        Some(file) => Some(metadata::load(file)?),
+        None => None
+    };
+    if options.hashes {
+        let hashes = inputs
+            .iter()
+            .zip(digests)
+            .map(|(v, digest)| (v.name.clone(), digest))
+            .collect();
+        insert_hashes(metadata.get_or_insert_with(Object::new), hashes);
+    }
+    if options.preserve {
+        let mut attributes = Vec::with_capacity(inputs.len());
+        for v in inputs {
+            attributes.push((
+                v.name.clone(),
+                read_attributes(v, options.source_date_epoch)?
+            ));
+        }
+        insert_attributes(metadata.get_or_insert_with(Object::new), attributes);
+    }
+    Ok(metadata)
+}
+
+//Approximate size of the BPX main header and of an entry of the BPXP object table
+const HEADER_SIZE: u64 = 40;
+const OBJECT_SIZE: u64 = 20;
+
+/// Estimates the size of a package made of `packed` before compression.
+///
+/// Section headers and metadata are not accounted for, so this is only an approximation.
+pub fn estimate_size(packed: &[Packed]) -> u64
+{
+    //Names are stored NUL terminated in the string section
+    packed
+        .iter()
+        .map(|v| v.size + OBJECT_SIZE + v.name.len() as u64 + 1)
+        .sum::<u64>()
+        + HEADER_SIZE
+}
+
+/// Packs `files` into the BPXP `file` and returns the packed objects in package order; each entry
+/// is a path, optionally followed by =name to choose the object name.
+///
+/// `on_packed` is called with each object once packed; it is not called for a dry run.
+///
+/// Packing runs on the calling thread: the BPXP encoder compresses each section itself when it is
+/// written and can't be given data compressed elsewhere, so unlike [unpack](super::unpack::run)
+/// compression can't be spread over worker threads.
+///
+/// Files with identical contents are each stored in full, as the encoder can't add an entry to the
+/// object table pointing to data which is already stored.
+pub fn run<F: FnMut(&Packed)>(
+    file: &Path,
+    files: &[&str],
+    options: &PackOptions,
+    mut on_packed: F
+) -> Result<Vec<Packed>, PackError>
+{
+    let mut sources: Vec<_> = files.iter().map(|v| parse_source(v)).collect();
+    if let Some(list) = options.files_from {
+        let entries = manifest::read(list)?;
+        sources.extend(entries.into_iter().map(|v| (v.source, v.name)));
+    }
+    //Problems (unreadable files, name collisions...) are reported before anything is written
+    let inputs = resolve_sources(sources.into_iter(), &options.input)?;
+    if inputs.is_empty() {
+        return Err(PackError::Input("no files to pack".into()));
+    }
+    let mut packed = Vec::with_capacity(inputs.len());
+    for v in &inputs {
+        packed.push(Packed {
+            source: v.source.clone(),
+            name: v.name.clone(),
+            size: v.size()?
+        });
+    }
+    if options.dry_run {
+        return Ok(packed);
+    }
+    let builder = create_builder(&options.build)?;
+    let total = packed.iter().map(|v| v.size).sum();
+    let progress = Progress::new(options.progress, inputs.len(), total);
+    let out = output::create(file)?;
+    let mut encoder = Package::create(out.reopen()?, builder)?;
+    let mut digests = Vec::new();
+
+    for (v, packed) in inputs.iter().zip(&packed) {
+        let reader = ProgressReader::new(v.open()?, &progress);
+        //Digests are computed from the packed data so that each file is read only once
+        if options.hashes {
+            let mut reader = HashReader::new(reader);
+            encoder.pack(&v.name, &mut reader)?;
+            digests.push(reader.finish());
+        } else {
+            encoder.pack(&v.name, reader)?;
+        }
+        progress.finish_object();
+        on_packed(packed);
+    }
+    //The metadata is written by save, after all objects
+    if let Some(metadata) = create_metadata(options, &inputs, digests)? {
+        encoder.set_metadata(metadata);
+    }
+    encoder.save()?;
+    drop(encoder);
+    output::persist(out, file)?;
+    progress.finish();
+    Ok(packed)
+}
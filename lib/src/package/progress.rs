@@ -0,0 +1,238 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    io::{IsTerminal, Read, Write},
+    sync::Mutex,
+    time::{Duration, Instant}
+};
+
+use crate::package::list::format_size;
+
+//Minimum delay between two progress updates
+const INTERVAL: Duration = Duration::from_millis(250);
+
+/// How progress of long operations is reported on standard error.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ProgressMode
+{
+    /// Display a progress line only when stderr is a terminal.
+    Auto,
+    /// Print one JSON object per update.
+    Json,
+    /// Do not report progress.
+    #[default]
+    None
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Mode
+{
+    Off,
+    Text,
+    Json
+}
+
+struct State
+{
+    objects: usize,
+    bytes: u64,
+    last: Option<Instant>
+}
+
+/// Shared by all worker threads.
+pub struct Progress
+{
+    mode: Mode,
+    total_objects: usize,
+    total_bytes: u64,
+    start: Instant,
+    state: Mutex<State>
+}
+
+fn format_duration(secs: u64) -> String
+{
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+impl Progress
+{
+    /// In [ProgressMode::Auto], progress is only displayed when stderr is a terminal.
+    pub fn new(mode: ProgressMode, total_objects: usize, total_bytes: u64) -> Progress
+    {
+        let mode = match mode {
+            ProgressMode::Json => Mode::Json,
+            ProgressMode::None => Mode::Off,
+            ProgressMode::Auto if std::io::stderr().is_terminal() => Mode::Text,
+            ProgressMode::Auto => Mode::Off
+        };
+        Progress {
+            mode,
+            total_objects,
+            total_bytes,
+            start: Instant::now(),
+            state: Mutex::new(State {
+                objects: 0,
+                bytes: 0,
+                last: None
+            })
+        }
+    }
+
+    pub fn add_bytes(&self, len: u64)
+    {
+        let mut state = self.state.lock().unwrap();
+        state.bytes += len;
+        self.update(&mut state, false);
+    }
+
+    pub fn finish_object(&self)
+    {
+        let mut state = self.state.lock().unwrap();
+        state.objects += 1;
+        self.update(&mut state, false);
+    }
+
+    pub fn finish(&self)
+    {
+        self.update(&mut self.state.lock().unwrap(), true);
+        if self.mode == Mode::Text {
+            eprintln!();
+        }
+    }
+
+    fn update(&self, state: &mut State, force: bool)
+    {
+        if self.mode == Mode::Off {
+            return;
+        }
+        let now = Instant::now();
+        if !force && state.last.map(|v| now - v < INTERVAL).unwrap_or_default() {
+            return;
+        }
+        state.last = Some(now);
+        let elapsed = (now - self.start).as_secs_f64();
+        let rate = if elapsed > 0.0 {
+            state.bytes as f64 / elapsed
+        } else {
+            0.0
+        };
+        let eta = if rate > 0.0 {
+            Some((self.total_bytes.saturating_sub(state.bytes) as f64 / rate) as u64)
+        } else {
+            None
+        };
+        match self.mode {
+            Mode::Json => eprintln!(
+                "{}",
+                serde_json::json!({
+                    "objects": state.objects,
+                    "total_objects": self.total_objects,
+                    "bytes": state.bytes,
+                    "total_bytes": self.total_bytes,
+                    "elapsed": elapsed,
+                    "rate": rate,
+                    "eta": eta
+                })
+            ),
+            _ => {
+                let percent = match self.total_bytes {
+                    0 => 100.0,
+                    total => state.bytes as f64 * 100.0 / total as f64
+                };
+                //\x1b[K clears what remains of the previous, possibly longer, line
+                eprint!(
+                    "\r{}/{} object(s), {} / {} ({:.0}%), {}/s, ETA {}\x1b[K",
+                    state.objects,
+                    self.total_objects,
+                    format_size(state.bytes, true),
+                    format_size(self.total_bytes, true),
+                    percent,
+                    format_size(rate as u64, true),
+                    eta.map(format_duration).unwrap_or_else(|| "-".into())
+                );
+                let _ = std::io::stderr().flush();
+            }
+        }
+    }
+}
+
+/// Reports the data read through it to a [Progress].
+pub struct ProgressReader<'a, R>
+{
+    inner: R,
+    progress: &'a Progress
+}
+
+impl<'a, R> ProgressReader<'a, R>
+{
+    pub fn new(inner: R, progress: &'a Progress) -> ProgressReader<'a, R>
+    {
+        ProgressReader { inner, progress }
+    }
+}
+
+impl<'a, R: Read> Read for ProgressReader<'a, R>
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize>
+    {
+        let len = self.inner.read(buf)?;
+        self.progress.add_bytes(len as u64);
+        Ok(len)
+    }
+}
+
+/// Reports the data written through it to a [Progress].
+pub struct ProgressWriter<'a, W>
+{
+    inner: W,
+    progress: &'a Progress
+}
+
+impl<'a, W> ProgressWriter<'a, W>
+{
+    pub fn new(inner: W, progress: &'a Progress) -> ProgressWriter<'a, W>
+    {
+        ProgressWriter { inner, progress }
+    }
+}
+
+impl<'a, W: Write> Write for ProgressWriter<'a, W>
+{
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize>
+    {
+        let len = self.inner.write(buf)?;
+        self.progress.add_bytes(len as u64);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()>
+    {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,280 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf}
+};
+
+use bpx::{core::Container, package::Package};
+use glob::{MatchOptions, Pattern};
+
+use crate::package::{
+    error::PackError,
+    list::SECTION_TYPE_DATA,
+    merge::{
+        collect_metadata,
+        copy_objects,
+        open_copy,
+        stored_build,
+        target_builder,
+        Decoder,
+        Policy
+    },
+    output,
+    pack::BuildOptions
+};
+
+//'*' stops at '/' while '**' matches any number of directories
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false
+};
+
+struct Entry
+{
+    name: String,
+    size: u64
+}
+
+fn list_entries(package: &mut Decoder) -> Result<Vec<Entry>, PackError>
+{
+    let mut entries = Vec::new();
+    for mut v in package.objects()? {
+        entries.push(Entry {
+            name: String::from(v.load_name()?),
+            size: v.size()
+        });
+    }
+    Ok(entries)
+}
+
+fn parse_rule(rule: &str) -> Result<(Pattern, PathBuf), PackError>
+{
+    let (pattern, output) = rule.split_once('=').ok_or_else(|| {
+        PackError::Input(format!("rule '{}' is not of the form pattern=output", rule))
+    })?;
+    let pattern = Pattern::new(pattern)
+        .map_err(|e| PackError::Input(format!("invalid pattern '{}' ({})", pattern, e)))?;
+    Ok((pattern, PathBuf::from(output)))
+}
+
+//Objects go to the output of the first matching rule
+fn split_by_rules<'a, I: Iterator<Item = &'a str>>(
+    entries: Vec<Entry>,
+    rules: I,
+    rest: Option<&Path>
+) -> Result<Vec<(PathBuf, Vec<String>)>, PackError>
+{
+    let rules = rules.map(parse_rule).collect::<Result<Vec<_>, _>>()?;
+    let mut outputs: Vec<(PathBuf, Vec<String>)> = Vec::new();
+    for entry in entries {
+        let output = match rules
+            .iter()
+            .find(|(pattern, _)| pattern.matches_with(&entry.name, MATCH_OPTIONS))
+        {
+            Some((_, output)) => output.clone(),
+            None => match rest {
+                Some(v) => v.to_path_buf(),
+                None => {
+                    return Err(PackError::Input(format!(
+                        "object '{}' matches no rule (use --rest to choose where to put unmatched objects)",
+                        entry.name
+                    )))
+                }
+            }
+        };
+        match outputs.iter_mut().find(|(v, _)| *v == output) {
+            Some((_, names)) => names.push(entry.name),
+            None => outputs.push((output, vec![entry.name]))
+        }
+    }
+    Ok(outputs)
+}
+
+fn numbered(file: &Path, index: usize) -> PathBuf
+{
+    let stem = file.file_stem().unwrap_or_default().to_string_lossy();
+    file.with_file_name(format!("{}.{}.bpx", stem, index))
+}
+
+//Everything but the object data: headers, object table, names and metadata. Each output holds a
+//subset of the objects and metadata of the split package, so this is a bound for every output
+fn overhead(file: &Path) -> Result<u64, PackError>
+{
+    let bpx = Container::open(BufReader::new(File::open(file)?))?;
+    let data: u64 = bpx
+        .iter()
+        .filter(|v| v.btype == SECTION_TYPE_DATA)
+        .map(|v| v.csize as u64)
+        .sum();
+    Ok(std::fs::metadata(file)?.len().saturating_sub(data))
+}
+
+//Objects are counted with their uncompressed size on top of the overhead of the split package;
+//this keeps a margin as long as compression does not expand the data, the actual size of each
+//output is checked once written
+fn split_by_size(
+    file: &Path,
+    entries: Vec<Entry>,
+    max_size: u64
+) -> Result<Vec<(PathBuf, Vec<String>)>, PackError>
+{
+    let overhead = overhead(file)?;
+    let mut outputs = Vec::new();
+    let mut names = Vec::new();
+    let mut size = overhead;
+    for entry in entries {
+        if overhead + entry.size > max_size {
+            return Err(PackError::Input(format!(
+                "object '{}' ({} byte(s)) does not fit in {} byte(s) ({} byte(s) are needed for headers and metadata)",
+                entry.name, entry.size, max_size, overhead
+            )));
+        }
+        if size + entry.size > max_size {
+            outputs.push((
+                numbered(file, outputs.len() + 1),
+                std::mem::take(&mut names)
+            ));
+            size = overhead;
+        }
+        size += entry.size;
+        names.push(entry.name);
+    }
+    if !names.is_empty() {
+        outputs.push((numbered(file, outputs.len() + 1), names));
+    }
+    Ok(outputs)
+}
+
+/// Options of [run]; objects are split by `rules` when given and by `max_size` otherwise.
+#[derive(Clone, Default)]
+pub struct SplitOptions<'a>
+{
+    /// Target, compression and checksum of the outputs; they default to those of the split
+    /// package, whose sections are then copied as they are stored. Objects are decoded and
+    /// compressed again when other settings are requested.
+    pub build: BuildOptions<'a>,
+    /// Rules of the form pattern=output.bpx, objects go to the output of the first matching glob
+    /// pattern.
+    pub rules: Vec<&'a str>,
+    /// Output receiving objects matching no rule.
+    pub rest: Option<&'a Path>,
+    /// Maximum size of each output, outputs are numbered after the split package; [run] fails
+    /// without writing anything when an output would be larger.
+    pub max_size: Option<u64>
+}
+
+/// A BPXP written by [run].
+pub struct SplitOutput
+{
+    pub path: PathBuf,
+    /// Names of the objects of the output.
+    pub objects: Vec<String>
+}
+
+/// Outputs written by [run].
+pub struct SplitReport
+{
+    /// Whether the sections of the split package were copied as stored; objects are decoded and
+    /// compressed again otherwise.
+    pub copied: bool,
+    pub outputs: Vec<SplitOutput>
+}
+
+/// Partitions the BPXP `file` into several BPXP keeping its metadata and target.
+pub fn run(file: &Path, options: &SplitOptions) -> Result<SplitReport, PackError>
+{
+    let max_size = options.max_size;
+    let source = file.to_string_lossy().into_owned();
+    let mut package = Package::open(BufReader::new(File::open(file)?))?;
+    let entries = list_entries(&mut package)?;
+    let outputs = if !options.rules.is_empty() {
+        split_by_rules(entries, options.rules.iter().copied(), options.rest)?
+    } else if let Some(max_size) = max_size {
+        split_by_size(file, entries, max_size)?
+    } else {
+        return Err(PackError::Input(
+            "please specify how to split the package with --rule or --max-size".into()
+        ));
+    };
+
+    let (build, copy) = stored_build(file, &options.build)?;
+    //Outputs are only persisted once all of them are written and fit in the maximum size
+    let mut written = Vec::with_capacity(outputs.len());
+    for (path, names) in &outputs {
+        let objects: Vec<(String, usize)> = names.iter().map(|v| (v.clone(), 0)).collect();
+        let selected: Vec<&str> = names.iter().map(String::as_str).collect();
+        let metadata = collect_metadata(
+            std::slice::from_mut(&mut package),
+            &[source.as_str()],
+            &objects,
+            Policy::Error
+        )?;
+        let out = output::create(path)?;
+        let mut encoder = if copy {
+            open_copy(&out, &mut package, file, &selected)?
+        } else {
+            let builder =
+                target_builder(std::slice::from_ref(&package), &[source.as_str()], &build)?;
+            let mut encoder = Package::create(out.reopen()?, builder)?;
+            copy_objects(&mut encoder, &mut package, &selected)?;
+            encoder
+        };
+        //The metadata is written by save, after all objects
+        if let Some(metadata) = metadata {
+            encoder.set_metadata(metadata);
+        }
+        encoder.save()?;
+        drop(encoder);
+        if let Some(max_size) = max_size {
+            let size = out.as_file().metadata()?.len();
+            if size > max_size {
+                return Err(PackError::Input(format!(
+                    "{} would be {} byte(s), above the requested maximum of {} byte(s)",
+                    path.display(),
+                    size,
+                    max_size
+                )));
+            }
+        }
+        written.push((out, path));
+    }
+    for (out, path) in written {
+        output::persist(out, path)?;
+    }
+    Ok(SplitReport {
+        copied: copy,
+        outputs: outputs
+            .into_iter()
+            .map(|(path, objects)| SplitOutput { path, objects })
+            .collect()
+    })
+}
@@ -0,0 +1,332 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, Read, Seek, Write},
+    path::{Component, Path, PathBuf}
+};
+
+use bpx::package::Package;
+
+use crate::package::{
+    attributes::AttributeTable,
+    error::UnpackError,
+    hash::{HashWriter, Hashes},
+    jobs::{self, Ordered},
+    progress::{Progress, ProgressMode, ProgressWriter}
+};
+
+/// An object written to the target directory.
+pub struct Unpacked
+{
+    pub name: String,
+    pub size: u64,
+    pub path: PathBuf
+}
+
+struct Job
+{
+    index: usize,
+    name: String,
+    size: u64
+}
+
+fn list_jobs<T: Read + Seek>(package: &mut Package<T>) -> Result<Vec<Job>, UnpackError>
+{
+    let mut jobs = Vec::new();
+    let mut unnamed_count = 0;
+    for (index, mut v) in package.objects()?.enumerate() {
+        let size = v.size();
+        let mut name: String = v.load_name()?.into();
+        if name.is_empty() {
+            unnamed_count += 1;
+            name = format!("unnamed_file_{}", unnamed_count);
+        }
+        jobs.push(Job { index, name, size });
+    }
+    Ok(jobs)
+}
+
+fn is_link(path: &Path) -> bool
+{
+    std::fs::symlink_metadata(path)
+        .map(|v| v.file_type().is_symlink())
+        .unwrap_or(false)
+}
+
+//Object names come from the package: each component must be a plain name and no object is ever
+//unpacked through a symbolic link, whether created by the package or already present
+fn destination(target: &Path, name: &str) -> Result<PathBuf, UnpackError>
+{
+    let mut dest = target.to_path_buf();
+    let mut components = name.split('/').peekable();
+    while let Some(component) = components.next() {
+        let mut parts = Path::new(component).components();
+        match (parts.next(), parts.next()) {
+            (Some(Component::Normal(v)), None) => dest.push(v),
+            _ => return Err(UnpackError::UnsafePath(name.into()))
+        }
+        if components.peek().is_some() && is_link(&dest) {
+            return Err(UnpackError::UnsafePath(name.into()));
+        }
+    }
+    Ok(dest)
+}
+
+//Maximum number of packaged links followed while resolving a link, like SYMLOOP_MAX
+const MAX_LINK_EXPANSIONS: usize = 40;
+
+//Resolves the target of the link `name` to its components in the target directory, following
+//the other packaged links it goes through; returns None if it escapes the target directory
+fn resolve_link(
+    links: &HashMap<&str, &str>,
+    name: &str,
+    expansions: &mut usize
+) -> Option<Vec<String>>
+{
+    let mut path: Vec<String> = name.split('/').map(String::from).collect();
+    path.pop();
+    for component in Path::new(links[name]).components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => {
+                path.pop()?;
+            },
+            Component::Normal(v) => {
+                path.push(v.to_string_lossy().into());
+                let current = path.join("/");
+                if links.contains_key(current.as_str()) {
+                    *expansions += 1;
+                    if *expansions > MAX_LINK_EXPANSIONS {
+                        return None;
+                    }
+                    path = resolve_link(links, &current, expansions)?;
+                }
+            },
+            _ => return None
+        }
+    }
+    Some(path)
+}
+
+//Links are created once all other objects are unpacked and are never followed afterwards, but a
+//link may still point through another packaged link
+fn check_links(links: &[(&str, &str)]) -> Result<(), UnpackError>
+{
+    let map = links.iter().copied().collect();
+    for (name, link) in links {
+        if resolve_link(&map, name, &mut 0).is_none() {
+            return Err(UnpackError::UnsafeLink(name.to_string(), link.to_string()));
+        }
+    }
+    Ok(())
+}
+
+//'f is the lifetime of the function reporting unpacked objects
+struct Context<'a, 'f>
+{
+    file: &'a Path,
+    target: &'a Path,
+    hashes: Option<Hashes>,
+    attributes: Option<AttributeTable>,
+    progress: &'a Progress,
+    unpacked: &'a Ordered<'f, Unpacked>
+}
+
+//Each thread decodes a contiguous range of the object table with its own package handle
+fn unpack_jobs(jobs: &[Job], ctx: &Context) -> Result<(), UnpackError>
+{
+    let first = match jobs.first() {
+        Some(v) => v.index,
+        None => return Ok(())
+    };
+    let mut package = Package::open(BufReader::new(File::open(ctx.file)?))?;
+    let objects = package.objects()?.enumerate().skip(first).take(jobs.len());
+    for (index, mut v) in objects {
+        let job = &jobs[index - first];
+        let attributes = ctx.attributes.as_ref().and_then(|v| v.get(&job.name));
+        //Links are reported once created, after all other objects
+        if attributes.as_ref().is_some_and(|v| v.link.is_some()) {
+            ctx.unpacked.done(index, None);
+            continue;
+        }
+        let dest = destination(ctx.target, &job.name)?;
+        if let Some(v) = dest.parent() {
+            std::fs::create_dir_all(v)?;
+        }
+        //An existing link is replaced rather than written through
+        if is_link(&dest) {
+            std::fs::remove_file(&dest)?;
+        }
+        let mut out = HashWriter::new(ProgressWriter::new(File::create(&dest)?, ctx.progress));
+        v.unpack(&mut out)?;
+        let hash = out.finish();
+        ctx.progress.finish_object();
+        if let Some(hashes) = &ctx.hashes {
+            hashes.check(&job.name, &hash)?;
+        }
+        if let Some(attributes) = attributes {
+            attributes.restore(&dest)?;
+        }
+        let unpacked = Unpacked {
+            name: job.name.clone(),
+            size: job.size,
+            path: dest
+        };
+        ctx.unpacked.done(index, Some(unpacked));
+    }
+    Ok(())
+}
+
+fn custom_unpack<T: Read + Seek>(
+    package: &mut Package<T>,
+    ctx: &Context,
+    jobs: usize
+) -> Result<(), UnpackError>
+{
+    let objects = list_jobs(package)?;
+    let mut links = Vec::new();
+    if let Some(attributes) = &ctx.attributes {
+        for v in &objects {
+            if let Some(link) = attributes.get(&v.name).and_then(|v| v.link) {
+                links.push((v, link));
+            }
+        }
+    }
+    check_links(
+        &links
+            .iter()
+            .map(|(v, link)| (v.name.as_str(), link.as_str()))
+            .collect::<Vec<_>>()
+    )?;
+    jobs::for_each_chunk(&objects, jobs, |chunk| unpack_jobs(chunk, ctx))?;
+    //Symbolic links are created last so that no object is unpacked through one
+    for (i, (v, _)) in links.into_iter().enumerate() {
+        let dest = destination(ctx.target, &v.name)?;
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        if let Some(attributes) = ctx.attributes.as_ref().and_then(|t| t.get(&v.name)) {
+            attributes.restore_link(&dest)?;
+        }
+        ctx.progress.finish_object();
+        let unpacked = Unpacked {
+            name: v.name.clone(),
+            size: v.size,
+            path: dest
+        };
+        ctx.unpacked.done(objects.len() + i, Some(unpacked));
+    }
+    Ok(())
+}
+
+/// Unpacks the object named `name` to `out`.
+pub(crate) fn find_unpack<T: Read + Seek, W: Write>(
+    package: &mut Package<T>,
+    name: &str,
+    out: W
+) -> Result<(), UnpackError>
+{
+    for mut v in package.objects()? {
+        if v.load_name()? == name {
+            v.unpack(out)?;
+            return Ok(());
+        }
+    }
+    Err(UnpackError::ObjectNotFound(name.into()))
+}
+
+/// Unpacks the object named `name` of the BPXP `file` to `out`.
+pub fn cat<W: Write>(file: &Path, name: &str, out: W) -> Result<(), UnpackError>
+{
+    let mut decoder = Package::open(BufReader::new(File::open(file)?))?;
+
+    find_unpack(&mut decoder, name, out)
+}
+
+/// Options of [run].
+#[derive(Clone)]
+pub struct UnpackOptions<'a>
+{
+    /// Directory receiving the objects.
+    pub target: &'a Path,
+    /// Restore file permissions, modification times and symbolic links.
+    pub preserve: bool,
+    pub progress: ProgressMode,
+    /// Number of threads decoding objects, 0 uses one thread per CPU.
+    pub jobs: usize
+}
+
+impl<'a> Default for UnpackOptions<'a>
+{
+    fn default() -> Self
+    {
+        UnpackOptions {
+            target: Path::new("."),
+            preserve: true,
+            progress: ProgressMode::default(),
+            jobs: 1
+        }
+    }
+}
+
+/// Unpacks all objects of the BPXP `file`, checking the digests stored in the package.
+///
+/// `on_unpacked` is called with each object once written, in package order whichever thread
+/// unpacks it, symbolic links last.
+pub fn run<F: FnMut(&Unpacked) + Send>(
+    file: &Path,
+    options: &UnpackOptions,
+    mut on_unpacked: F
+) -> Result<(), UnpackError>
+{
+    let mut decoder = Package::open(BufReader::new(File::open(file)?))?;
+    let (count, total) = decoder
+        .objects()?
+        .fold((0, 0), |(count, total), v| (count + 1, total + v.size()));
+    let progress = Progress::new(options.progress, count, total);
+    let unpacked = Ordered::new(|v: Unpacked| on_unpacked(&v));
+    let ctx = Context {
+        file,
+        target: options.target,
+        hashes: Hashes::read(decoder.get_metadata()?),
+        attributes: if options.preserve {
+            AttributeTable::read(decoder.get_metadata()?)
+        } else {
+            None
+        },
+        progress: &progress,
+        unpacked: &unpacked
+    };
+
+    custom_unpack(&mut decoder, &ctx, options.jobs)?;
+    progress.finish();
+    Ok(())
+}
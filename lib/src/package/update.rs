@@ -0,0 +1,271 @@
+// Copyright (c) 2021, BlockProject 3D
+//
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without modification,
+// are permitted provided that the following conditions are met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//     * Redistributions in binary form must reproduce the above copyright notice,
+//       this list of conditions and the following disclaimer in the documentation
+//       and/or other materials provided with the distribution.
+//     * Neither the name of BlockProject 3D nor the names of its contributors
+//       may be used to endorse or promote products derived from this software
+//       without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
+// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
+// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
+// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    path::{Path, PathBuf}
+};
+
+use bpx::{package::Package, sd::Object};
+
+use crate::package::{
+    attributes::{insert_attributes, AttributeTable},
+    error::PackError,
+    hash::{insert_hashes, HashReader, Hashes},
+    input::{resolve, Input, InputOptions},
+    output,
+    pack::read_attributes
+};
+
+struct Changes<'a>
+{
+    add: Vec<Input>,
+    replace: HashMap<String, Input>,
+    delete: Vec<&'a str>
+}
+
+/// Changes applied by [run]; added and replacing files are given as path or path=name.
+#[derive(Clone)]
+pub struct UpdateOptions<'a>
+{
+    pub add: Vec<&'a str>,
+    pub replace: Vec<&'a str>,
+    /// Names of the objects to delete.
+    pub delete: Vec<&'a str>,
+    /// Naming of the added and replacing files.
+    pub input: InputOptions<'a>,
+    /// Store the attributes of the added and replacing files if the package stores attributes.
+    pub preserve: bool,
+    /// Clamp stored modification times to this UNIX timestamp.
+    pub source_date_epoch: Option<i64>
+}
+
+impl<'a> Default for UpdateOptions<'a>
+{
+    fn default() -> Self
+    {
+        UpdateOptions {
+            add: Vec::new(),
+            replace: Vec::new(),
+            delete: Vec::new(),
+            input: InputOptions::default(),
+            preserve: true,
+            source_date_epoch: None
+        }
+    }
+}
+
+/// A change applied by [run], in the order changes are applied.
+pub enum Change
+{
+    /// Name of the deleted object.
+    Deleted(String),
+    /// Name of the replaced object and the file replacing it.
+    Replaced(String, PathBuf),
+    /// Added file and the name of its object.
+    Added(PathBuf, String)
+}
+
+fn read_changes<'a>(options: &UpdateOptions<'a>) -> Result<Changes<'a>, PackError>
+{
+    let replace = resolve(options.replace.iter().copied(), &options.input)?
+        .into_iter()
+        .map(|v| (v.name.clone(), v))
+        .collect();
+    Ok(Changes {
+        add: resolve(options.add.iter().copied(), &options.input)?,
+        replace,
+        delete: options.delete.clone()
+    })
+}
+
+//Keeps the digests and attributes of unchanged objects and sets those of replaced and added
+//objects from the digests computed while packing them and the attribute policy of pack
+fn update_metadata(
+    names: &[String],
+    metadata: &mut Object,
+    changes: &Changes,
+    options: &UpdateOptions,
+    mut digests: HashMap<String, String>
+) -> Result<(), PackError>
+{
+    let hashes = Hashes::read(Some(metadata));
+    let attributes = AttributeTable::read(Some(metadata));
+    if hashes.is_none() && attributes.is_none() {
+        return Ok(());
+    }
+    let mut new_hashes = Vec::new();
+    let mut new_attributes = Vec::new();
+    let names = names
+        .iter()
+        .filter(|v| !changes.delete.contains(&v.as_str()))
+        .cloned();
+    for name in names {
+        match changes.replace.get(&name) {
+            Some(input) => {
+                if let Some(digest) = digests.remove(&name) {
+                    new_hashes.push((name.clone(), digest));
+                }
+                if attributes.is_some() && options.preserve {
+                    new_attributes.push((name, read_attributes(input, options.source_date_epoch)?));
+                }
+            },
+            None => {
+                if let Some(hash) = hashes.as_ref().and_then(|v| v.get(&name)) {
+                    new_hashes.push((name.clone(), hash.into()));
+                }
+                if let Some(attrs) = attributes.as_ref().and_then(|v| v.get(&name)) {
+                    new_attributes.push((name, attrs));
+                }
+            }
+        }
+    }
+    for v in &changes.add {
+        if let Some(digest) = digests.remove(&v.name) {
+            new_hashes.push((v.name.clone(), digest));
+        }
+        if attributes.is_some() && options.preserve {
+            new_attributes.push((
+                v.name.clone(),
+                read_attributes(v, options.source_date_epoch)?
+            ));
+        }
+    }
+    if hashes.is_some() {
+        insert_hashes(metadata, new_hashes);
+    }
+    if attributes.is_some() {
+        insert_attributes(metadata, new_attributes);
+    }
+    Ok(())
+}
+
+//Digests are computed from the packed data so that each file is read only once
+fn pack_input(
+    package: &mut Package<File>,
+    name: &str,
+    input: &Input,
+    digests: Option<&mut HashMap<String, String>>
+) -> Result<(), PackError>
+{
+    match digests {
+        Some(digests) => {
+            let mut reader = HashReader::new(input.open()?);
+            package.pack(name, &mut reader)?;
+            digests.insert(name.into(), reader.finish());
+        },
+        None => package.pack(name, input.open()?)?
+    }
+    Ok(())
+}
+
+fn missing(names: Vec<&str>, what: &str) -> Result<(), PackError>
+{
+    match names.first() {
+        None => Ok(()),
+        Some(name) => Err(PackError::Input(format!(
+            "cannot {} '{}': no such object in package",
+            what, name
+        )))
+    }
+}
+
+/// Adds, replaces and deletes objects of the BPXP `file` in place.
+///
+/// The package keeps its target, compression and checksum; sections of unchanged objects are
+/// written back as they are stored, without being decoded.
+pub fn run(file: &Path, options: &UpdateOptions) -> Result<Vec<Change>, PackError>
+{
+    let changes = read_changes(options)?;
+    let out = output::create(file)?;
+    std::io::copy(&mut File::open(file)?, &mut out.reopen()?)?;
+    let mut package = Package::open(out.reopen()?)?;
+    let mut names = Vec::new();
+    for mut v in package.objects()? {
+        names.push(String::from(v.load_name()?));
+    }
+    if let Some(input) = changes.add.iter().find(|v| names.contains(&v.name)) {
+        return Err(PackError::Input(format!(
+            "cannot add {}: object '{}' already exists in package",
+            input.source.display(),
+            input.name
+        )));
+    }
+    missing(
+        changes
+            .delete
+            .iter()
+            .copied()
+            .filter(|v| !names.iter().any(|n| n == v))
+            .collect(),
+        "delete"
+    )?;
+    missing(
+        changes
+            .replace
+            .keys()
+            .map(|v| v.as_str())
+            .filter(|v| !names.iter().any(|n| n == v))
+            .collect(),
+        "replace"
+    )?;
+    let metadata = package.get_metadata()?.cloned();
+    let mut digests = Hashes::read(metadata.as_ref()).map(|_| HashMap::new());
+    let mut applied = Vec::new();
+
+    for name in &changes.delete {
+        package.remove_object(name)?;
+        applied.push(Change::Deleted(name.to_string()));
+    }
+    //Replaced objects are moved to the end of the object table
+    for name in names.iter().filter(|v| changes.replace.contains_key(*v)) {
+        let input = &changes.replace[name];
+        package.remove_object(name)?;
+        pack_input(&mut package, name, input, digests.as_mut())?;
+        applied.push(Change::Replaced(name.clone(), input.source.clone()));
+    }
+    for v in &changes.add {
+        pack_input(&mut package, &v.name, v, digests.as_mut())?;
+        applied.push(Change::Added(v.source.clone(), v.name.clone()));
+    }
+    if let Some(mut metadata) = metadata {
+        update_metadata(
+            &names,
+            &mut metadata,
+            &changes,
+            options,
+            digests.unwrap_or_default()
+        )?;
+        package.set_metadata(metadata);
+    }
+    package.save()?;
+    drop(package);
+    output::persist(out, file)?;
+    Ok(applied)
+}
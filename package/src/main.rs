@@ -1,84 +0,0 @@
-// Copyright (c) 2021, BlockProject 3D
-//
-// All rights reserved.
-//
-// Redistribution and use in source and binary forms, with or without modification,
-// are permitted provided that the following conditions are met:
-//
-//     * Redistributions of source code must retain the above copyright notice,
-//       this list of conditions and the following disclaimer.
-//     * Redistributions in binary form must reproduce the above copyright notice,
-//       this list of conditions and the following disclaimer in the documentation
-//       and/or other materials provided with the distribution.
-//     * Neither the name of BlockProject 3D nor the names of its contributors
-//       may be used to endorse or promote products derived from this software
-//       without specific prior written permission.
-//
-// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS
-// "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT
-// LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR
-// A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT OWNER OR
-// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
-// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
-// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
-// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
-// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
-// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
-// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
-
-use std::path::Path;
-
-use clap::clap_app;
-
-mod error;
-mod list;
-mod pack;
-mod unpack;
-
-fn main()
-{
-    //The verbose option cannot be implemented yet due to the inflexibility of the BPXP format
-    //The BPXP format cannot yet deal with random file access: a single file cannot be accessed without unpacking the whole archive
-    let matches = clap_app!(bpxp =>
-        (version: "1.0")
-        (author: "BlockProject3D <https://github.com/BlockProject3D>")
-        (about: "Manages BPX type P (Package) files")
-        (@arg verbose: -v --verbose "Print debug information when packing or unpacking objects")
-        (@arg file: -f --file +required +takes_value "Path to the output/input BPX file")
-        (@arg unpack: -u --unpack "Indicates to run the unpacker")
-        (@arg pack: -p --pack "Indicates to run the packer")
-        (@arg ls: -l --list "List all objects contained in that BPXP")
-        (@arg files: ... "List of files/objects to pack")
-    )
-    .get_matches();
-    let file = matches.value_of("file").unwrap();
-
-    if matches.is_present("unpack") {
-        match unpack::run(Path::new(file), matches.is_present("verbose")) {
-            Ok(()) => std::process::exit(0),
-            Err(e) => {
-                eprintln!("{}", e);
-                std::process::exit(1);
-            }
-        }
-    } else if matches.is_present("pack") {
-        match pack::run(Path::new(file), &matches) {
-            Ok(()) => std::process::exit(0),
-            Err(e) => {
-                eprintln!("{}", e);
-                std::process::exit(1);
-            }
-        }
-    } else if matches.is_present("ls") {
-        match list::run(Path::new(file)) {
-            Ok(()) => std::process::exit(0),
-            Err(e) => {
-                eprintln!("{}", e);
-                std::process::exit(1);
-            }
-        }
-    } else {
-        eprintln!("Please specify an action");
-        std::process::exit(2);
-    }
-}